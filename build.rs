@@ -1,7 +1,9 @@
 extern crate phf_codegen;
 
+use std::collections::{HashMap, HashSet};
 use std::env;
-use std::fs::File;
+use std::fmt;
+use std::fs::{self, File};
 use std::io::{BufWriter, Write};
 use std::path::Path;
 
@@ -9,15 +11,15 @@ trait Map<'a> {
     fn gen(&self, from: Vec<ScriptTup<'a>>, invert: bool) -> phf_codegen::Map<&'a str> {
         let mut map = phf_codegen::Map::new();
 
-        for &ScriptTup(key, value) in from.iter() {
+        for &ScriptTup(key, value, _) in from.iter() {
             if invert {
                 // NOTE:
                 // How is &format allowed here?
                 // The string will destruct after the function body is finished
                 // Wouldn't that mean the map then points to something that doesn't exist since we lend a string reference?
-                map.entry(value, &format!("\"{}\"", key));
+                map.entry(value, &format!("{:?}", key));
             } else {
-                map.entry(key, &format!("\"{}\"", value));
+                map.entry(key, &format!("{:?}", value));
             }
         }
 
@@ -41,11 +43,64 @@ trait Gen<'a> {
 }
 
 #[derive(Clone)]
-struct ScriptTup<'a>(&'a str, &'a str);
+struct ScriptTup<'a>(&'a str, &'a str, Origin);
+
+// Where a pair was read from, for error reports
+#[derive(Clone, Copy)]
+struct Origin {
+    path: &'static str,
+    line: usize,
+}
+
+impl fmt::Display for Origin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "data/{}:{}", self.path, self.line)
+    }
+}
+
+// Read a table from data/, one `from<TAB>to` pair per line.
+// Blank lines and lines starting with # are skipped.
+//
+// The contents are leaked so the pairs can be borrowed for the rest of the build,
+// like the string literals they used to be.
+fn load(path: &'static str) -> Vec<ScriptTup<'static>> {
+    let full_path = Path::new(&env::var("CARGO_MANIFEST_DIR").unwrap())
+        .join("data")
+        .join(path);
+    let contents = fs::read_to_string(&full_path)
+        .unwrap_or_else(|e| panic!("Unable to read data/{}: {}", path, e));
+    let contents: &'static str = Box::leak(contents.into_boxed_str());
+
+    contents
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty() && !line.starts_with('#'))
+        .map(|(index, line)| {
+            let line = line.trim_end_matches('\r');
+
+            match line.split('\t').collect::<Vec<_>>()[..] {
+                [from, to] if !from.is_empty() && !to.is_empty() => ScriptTup(
+                    from,
+                    to,
+                    Origin {
+                        path,
+                        line: index + 1,
+                    },
+                ),
+                _ => panic!(
+                    "data/{}:{}: expected two non-empty tab-separated columns, found {:?}",
+                    path,
+                    index + 1,
+                    line
+                ),
+            }
+        })
+        .collect()
+}
 
 struct Geminates<'a> {
-    dominant: [ScriptTup<'a>; 9],
-    rest: [ScriptTup<'a>; 3],
+    dominant: Vec<ScriptTup<'a>>,
+    rest: Vec<ScriptTup<'a>>,
 }
 
 impl Map<'_> for Geminates<'_> {}
@@ -53,16 +108,21 @@ impl Map<'_> for Geminates<'_> {}
 impl Gen<'_> for Geminates<'_> {}
 
 impl<'a> Geminates<'a> {
+    // In the form of: romaji → partial kana
+    fn load() -> Self {
+        Geminates {
+            dominant: load("geminates/dominant.tsv"),
+            rest: load("geminates/rest.tsv"),
+        }
+    }
+
     // combine the dominant + rest and generate a Map
-    fn romaji_to_partial_hiragana(self, buf: &mut BufWriter<File>) {
+    fn romaji_to_partial_hiragana(&self, buf: &mut BufWriter<File>) {
         let data = self.gen(
-            // NOTE:
-            // Is there a better way to concatenate these arrays?
             self.dominant
-                .to_vec()
                 .iter()
+                .chain(self.rest.iter())
                 .cloned()
-                .chain(self.rest.to_vec().iter().cloned())
                 .collect(),
             false,
         );
@@ -71,39 +131,22 @@ impl<'a> Geminates<'a> {
     }
 
     // generate a Map from only the dominant Geminates
-    fn partial_hiragana_to_romaji(self, buf: &mut BufWriter<File>) {
-        let data = self.gen(self.dominant.to_vec().iter().cloned().collect(), true);
+    fn partial_hiragana_to_romaji(&self, buf: &mut BufWriter<File>) {
+        let data = self.gen(self.dominant.clone(), true);
 
         self.write("HIRAGANA_TO_GEMINATES", buf, &data)
     }
 }
 
-// In the form of: romaji → partial kana
-const GEMINATES: Geminates = Geminates {
-    dominant: [
-        ScriptTup("kk", "っk"),
-        ScriptTup("tt", "っt"),
-        ScriptTup("cc", "っc"),
-        ScriptTup("ss", "っs"),
-        ScriptTup("pp", "っp"),
-        ScriptTup("mm", "んm"),
-        ScriptTup("mt", "んt"),
-        ScriptTup("mb", "んb"),
-        ScriptTup("mp", "んp"),
-    ],
-
-    rest: [
-        ScriptTup("nt", "んt"),
-        ScriptTup("np", "んp"),
-        ScriptTup("nb", "んb"),
-    ],
-};
-
 struct Romaji<'a> {
-    dominant: [ScriptTup<'a>; 205],
-    rest: [ScriptTup<'a>; 70],
-    with_sokuon: [ScriptTup<'a>; 3],
-    punctuation: [ScriptTup<'a>; 6],
+    dominant: Vec<ScriptTup<'a>>,
+    rest: Vec<ScriptTup<'a>>,
+    with_sokuon: Vec<ScriptTup<'a>>,
+    punctuation: Vec<ScriptTup<'a>>,
+    // Kana deliberately left without a romaji spelling, with the reason why
+    unwritten: Vec<ScriptTup<'a>>,
+    // Hepburn spellings of extended kana for loanwords, opted into by a Converter
+    loanwords: Vec<ScriptTup<'a>>,
 }
 
 impl Map<'_> for Romaji<'_> {}
@@ -119,22 +162,26 @@ impl Gen<'_> for Romaji<'_> {}
 // No. 2 ensures that the "dominant" mapping is preferred
 // For eg, "ka" over "ca" for "か"
 impl<'a> Romaji<'a> {
-    // NOTE:
-    // How am I allowed to take self as the first parameter here?
-    // Shouldn't this be &self?
-    // This would mean that I'm passing ownership to this method?
-    // But how does that work when my struct is actually a const (see down below)?
+    fn load() -> Self {
+        Romaji {
+            dominant: load("romaji/dominant.tsv"),
+            rest: load("romaji/rest.tsv"),
+            with_sokuon: load("romaji/with_sokuon.tsv"),
+            punctuation: load("romaji/punctuation.tsv"),
+            unwritten: load("romaji/unwritten.tsv"),
+            loanwords: load("romaji/loanwords.tsv"),
+        }
+    }
 
     // combine dominant + rest + with_sukuon + punction
-    fn romaji_to_hiragana(self, buf: &mut BufWriter<File>) {
+    fn romaji_to_hiragana(&self, buf: &mut BufWriter<File>) {
         let data = self.gen(
             self.dominant
-                .to_vec()
                 .iter()
+                .chain(self.rest.iter())
+                .chain(self.with_sokuon.iter())
+                .chain(self.punctuation.iter())
                 .cloned()
-                .chain(self.rest.to_vec().iter().cloned())
-                .chain(self.with_sokuon.to_vec().iter().cloned())
-                .chain(self.punctuation.to_vec().iter().cloned())
                 .collect(),
             false,
         );
@@ -143,441 +190,171 @@ impl<'a> Romaji<'a> {
     }
 
     // combine dominant + puncation and elide rest + with_sukuon
-    fn hiragana_to_romaji(self, buf: &mut BufWriter<File>) {
+    fn hiragana_to_romaji(&self, buf: &mut BufWriter<File>) {
         let data = self.gen(
             self.dominant
-                .to_vec()
                 .iter()
+                .chain(self.punctuation.iter())
                 .cloned()
-                .chain(self.punctuation.to_vec().iter().cloned())
                 .collect(),
             true,
         );
 
         self.write("HIRAGANA_TO_ROMAJI", buf, &data)
     }
-}
 
-const ROMAJI: Romaji = Romaji {
-    dominant: [
-        ScriptTup("n", "ん"),
-        ScriptTup("nnn", "んn"),
-        ScriptTup("a", "あ"),
-        ScriptTup("i", "い"),
-        ScriptTup("u", "う"),
-        ScriptTup("e", "え"),
-        ScriptTup("o", "お"),
-        ScriptTup("la", "ぁ"),
-        ScriptTup("li", "ぃ"),
-        ScriptTup("lu", "ぅ"),
-        ScriptTup("le", "ぇ"),
-        ScriptTup("lo", "ぉ"),
-        ScriptTup("ye", "いぇ"),
-        ScriptTup("wi", "うぃ"),
-        ScriptTup("we", "うぇ"),
-        ScriptTup("wha", "うぁ"),
-        ScriptTup("ka", "か"),
-        ScriptTup("ki", "き"),
-        ScriptTup("ku", "く"),
-        ScriptTup("ke", "け"),
-        ScriptTup("ko", "こ"),
-        ScriptTup("kya", "きゃ"),
-        ScriptTup("kyi", "きぃ"),
-        ScriptTup("kyu", "きゅ"),
-        ScriptTup("kye", "きぇ"),
-        ScriptTup("kyo", "きょ"),
-        ScriptTup("qya", "くゃ"),
-        ScriptTup("qyu", "くゅ"),
-        ScriptTup("qyo", "くょ"),
-        ScriptTup("lka", "ヵ"),
-        ScriptTup("qwa", "くぁ"),
-        ScriptTup("qwi", "くぃ"),
-        ScriptTup("qwu", "くぅ"),
-        ScriptTup("qwe", "くぇ"),
-        ScriptTup("qwo", "くぉ"),
-        ScriptTup("ga", "が"),
-        ScriptTup("gi", "ぎ"),
-        ScriptTup("gu", "ぐ"),
-        ScriptTup("ge", "げ"),
-        ScriptTup("go", "ご"),
-        ScriptTup("gya", "ぎゃ"),
-        ScriptTup("gyi", "ぎぃ"),
-        ScriptTup("gyu", "ぎゅ"),
-        ScriptTup("gye", "ぎぇ"),
-        ScriptTup("gyo", "ぎょ"),
-        ScriptTup("gwa", "ぐぁ"),
-        ScriptTup("gwi", "ぐぃ"),
-        ScriptTup("gwu", "ぐぅ"),
-        ScriptTup("gwe", "ぐぇ"),
-        ScriptTup("gwo", "ぐぉ"),
-        ScriptTup("shi", "し"),
-        ScriptTup("sa", "さ"),
-        ScriptTup("su", "す"),
-        ScriptTup("se", "せ"),
-        ScriptTup("so", "そ"),
-        ScriptTup("sha", "しゃ"),
-        ScriptTup("shu", "しゅ"),
-        ScriptTup("she", "しぇ"),
-        ScriptTup("sho", "しょ"),
-        ScriptTup("syi", "しぃ"),
-        ScriptTup("swa", "すぁ"),
-        ScriptTup("swi", "すぃ"),
-        ScriptTup("swu", "すぅ"),
-        ScriptTup("swe", "すぇ"),
-        ScriptTup("swo", "すぉ"),
-        ScriptTup("ji", "じ"),
-        ScriptTup("za", "ざ"),
-        ScriptTup("zu", "ず"),
-        ScriptTup("ze", "ぜ"),
-        ScriptTup("zo", "ぞ"),
-        ScriptTup("ja", "じゃ"),
-        ScriptTup("ju", "じゅ"),
-        ScriptTup("je", "じぇ"),
-        ScriptTup("jo", "じょ"),
-        ScriptTup("jyi", "じぃ"),
-        ScriptTup("chi", "ち"),
-        ScriptTup("tsu", "つ"),
-        ScriptTup("ta", "た"),
-        ScriptTup("te", "て"),
-        ScriptTup("to", "と"),
-        ScriptTup("cha", "ちゃ"),
-        ScriptTup("chu", "ちゅ"),
-        ScriptTup("che", "ちぇ"),
-        ScriptTup("cho", "ちょ"),
-        ScriptTup("tyi", "ちぃ"),
-        ScriptTup("tsa", "つぁ"),
-        ScriptTup("tsi", "つぃ"),
-        ScriptTup("tse", "つぇ"),
-        ScriptTup("tso", "つぉ"),
-        ScriptTup("tha", "てゃ"),
-        ScriptTup("thi", "てぃ"),
-        ScriptTup("thu", "てゅ"),
-        ScriptTup("the", "てぇ"),
-        ScriptTup("tho", "てょ"),
-        ScriptTup("twa", "とぁ"),
-        ScriptTup("twi", "とぃ"),
-        ScriptTup("twu", "とぅ"),
-        ScriptTup("twe", "とぇ"),
-        ScriptTup("two", "とぉ"),
-        ScriptTup("dzu", "づ"),
-        ScriptTup("dzi", "ぢ"),
-        ScriptTup("da", "だ"),
-        ScriptTup("de", "で"),
-        ScriptTup("do", "ど"),
-        ScriptTup("dya", "ぢゃ"),
-        ScriptTup("dyi", "ぢぃ"),
-        ScriptTup("dyu", "ぢゅ"),
-        ScriptTup("dye", "ぢぇ"),
-        ScriptTup("dyo", "ぢょ"),
-        ScriptTup("dha", "でゃ"),
-        ScriptTup("dhi", "でぃ"),
-        ScriptTup("dhu", "でゅ"),
-        ScriptTup("dhe", "でぇ"),
-        ScriptTup("dho", "でょ"),
-        ScriptTup("dwa", "どぁ"),
-        ScriptTup("dwi", "どぃ"),
-        ScriptTup("dwu", "どぅ"),
-        ScriptTup("dwe", "どぇ"),
-        ScriptTup("dwo", "どぉ"),
-        ScriptTup("na", "な"),
-        ScriptTup("ni", "に"),
-        ScriptTup("nu", "ぬ"),
-        ScriptTup("ne", "ね"),
-        ScriptTup("no", "の"),
-        ScriptTup("nya", "にゃ"),
-        ScriptTup("nyi", "にぃ"),
-        ScriptTup("nyu", "にゅ"),
-        ScriptTup("nye", "にぇ"),
-        ScriptTup("nyo", "にょ"),
-        ScriptTup("fu", "ふ"),
-        ScriptTup("ha", "は"),
-        ScriptTup("hi", "ひ"),
-        ScriptTup("he", "へ"),
-        ScriptTup("ho", "ほ"),
-        ScriptTup("hya", "ひゃ"),
-        ScriptTup("hyi", "ひぃ"),
-        ScriptTup("hyu", "ひゅ"),
-        ScriptTup("hye", "ひぇ"),
-        ScriptTup("hyo", "ひょ"),
-        ScriptTup("fya", "ふゃ"),
-        ScriptTup("fyi", "ふぃ"),
-        ScriptTup("fyu", "ふゅ"),
-        ScriptTup("fye", "ふぇ"),
-        ScriptTup("fyo", "ふょ"),
-        ScriptTup("fa", "ふぁ"),
-        ScriptTup("fo", "ふぉ"),
-        ScriptTup("ba", "ば"),
-        ScriptTup("bi", "び"),
-        ScriptTup("bu", "ぶ"),
-        ScriptTup("be", "べ"),
-        ScriptTup("bo", "ぼ"),
-        ScriptTup("bya", "びゃ"),
-        ScriptTup("byi", "びぃ"),
-        ScriptTup("byu", "びゅ"),
-        ScriptTup("bye", "びぇ"),
-        ScriptTup("byo", "びょ"),
-        ScriptTup("va", "ヴぁ"),
-        ScriptTup("vu", "ヴ"),
-        ScriptTup("vo", "ヴぉ"),
-        ScriptTup("vya", "ヴゃ"),
-        ScriptTup("vi", "ヴぃ"),
-        ScriptTup("vyu", "ヴゅ"),
-        ScriptTup("ve", "ヴぇ"),
-        ScriptTup("vyo", "ヴょ"),
-        ScriptTup("pa", "ぱ"),
-        ScriptTup("pi", "ぴ"),
-        ScriptTup("pu", "ぷ"),
-        ScriptTup("pe", "ぺ"),
-        ScriptTup("po", "ぽ"),
-        ScriptTup("pya", "ぴゃ"),
-        ScriptTup("pyi", "ぴぃ"),
-        ScriptTup("pyu", "ぴゅ"),
-        ScriptTup("pye", "ぴぇ"),
-        ScriptTup("pyo", "ぴょ"),
-        ScriptTup("ma", "ま"),
-        ScriptTup("mi", "み"),
-        ScriptTup("mu", "む"),
-        ScriptTup("me", "め"),
-        ScriptTup("mo", "も"),
-        ScriptTup("mya", "みゃ"),
-        ScriptTup("myi", "みぃ"),
-        ScriptTup("myu", "みゅ"),
-        ScriptTup("mye", "みぇ"),
-        ScriptTup("myo", "みょ"),
-        ScriptTup("ya", "や"),
-        ScriptTup("yu", "ゆ"),
-        ScriptTup("yo", "よ"),
-        ScriptTup("lya", "ゃ"),
-        ScriptTup("lyu", "ゅ"),
-        ScriptTup("lyo", "ょ"),
-        ScriptTup("ra", "ら"),
-        ScriptTup("ri", "り"),
-        ScriptTup("ru", "る"),
-        ScriptTup("re", "れ"),
-        ScriptTup("ro", "ろ"),
-        ScriptTup("rya", "りゃ"),
-        ScriptTup("ryi", "りぃ"),
-        ScriptTup("ryu", "りゅ"),
-        ScriptTup("rye", "りぇ"),
-        ScriptTup("ryo", "りょ"),
-        ScriptTup("wa", "わ"),
-        ScriptTup("wyi", "ゐ"),
-        ScriptTup("wye", "ゑ"),
-        ScriptTup("wo", "を"),
-        ScriptTup("lwa", "ゎ"),
-    ],
-
-    rest: [
-        ScriptTup("vye", "ヴぇ"),
-        ScriptTup("vyi", "ヴぃ"),
-        ScriptTup("fi", "ふぃ"),
-        ScriptTup("fe", "ふぇ"),
-        ScriptTup("hu", "ふ"),
-        ScriptTup("di", "ぢ"),
-        ScriptTup("du", "づ"),
-        ScriptTup("cye", "ちぇ"),
-        ScriptTup("cyo", "ちょ"),
-        ScriptTup("tyu", "ちゅ"),
-        ScriptTup("cyu", "ちゅ"),
-        ScriptTup("tye", "ちぇ"),
-        ScriptTup("tyo", "ちょ"),
-        ScriptTup("cyi", "ちぃ"),
-        ScriptTup("tya", "ちゃ"),
-        ScriptTup("cya", "ちゃ"),
-        ScriptTup("ti", "ち"),
-        ScriptTup("tu", "つ"),
-        ScriptTup("zi", "じ"),
-        ScriptTup("sya", "しゃ"),
-        ScriptTup("syu", "しゅ"),
-        ScriptTup("sye", "しぇ"),
-        ScriptTup("syo", "しょ"),
-        ScriptTup("jya", "じゃ"),
-        ScriptTup("jyu", "じゅ"),
-        ScriptTup("jye", "じぇ"),
-        ScriptTup("jyo", "じょ"),
-        ScriptTup("zya", "じゃ"),
-        ScriptTup("zyi", "じぃ"),
-        ScriptTup("zyu", "じゅ"),
-        ScriptTup("zye", "じぇ"),
-        ScriptTup("zyo", "じょ"),
-        ScriptTup("si", "し"),
-        ScriptTup("ci", "し"),
-        ScriptTup("ce", "せ"),
-        ScriptTup("xka", "ヵ"),
-        ScriptTup("qyi", "くぃ"),
-        ScriptTup("qye", "くぇ"),
-        ScriptTup("kwa", "くぁ"),
-        ScriptTup("qa", "くぁ"),
-        ScriptTup("qi", "くぃ"),
-        ScriptTup("qe", "くぇ"),
-        ScriptTup("qo", "くぉ"),
-        ScriptTup("xke", "ヶ"),
-        ScriptTup("lke", "ヶ"),
-        ScriptTup("ca", "か"),
-        ScriptTup("cu", "く"),
-        ScriptTup("co", "こ"),
-        ScriptTup("qu", "く"),
-        ScriptTup("whi", "うぃ"),
-        ScriptTup("whe", "うぇ"),
-        ScriptTup("who", "うぉ"),
-        ScriptTup("yi", "い"),
-        ScriptTup("lyi", "ぃ"),
-        ScriptTup("xyi", "ぃ"),
-        ScriptTup("lye", "ぇ"),
-        ScriptTup("xye", "ぇ"),
-        ScriptTup("xa", "ぁ"),
-        ScriptTup("xi", "ぃ"),
-        ScriptTup("xu", "ぅ"),
-        ScriptTup("xe", "ぇ"),
-        ScriptTup("xo", "ぉ"),
-        ScriptTup("whu", "う"),
-        ScriptTup("nn", "ん"),
-        ScriptTup("xn", "ん"),
-        ScriptTup("wu", "う"),
-        ScriptTup("xya", "ゃ"),
-        ScriptTup("xyu", "ゅ"),
-        ScriptTup("xyo", "ょ"),
-        ScriptTup("xwa", "ゎ"),
-    ],
-
-    with_sokuon: [
-        ScriptTup("ltu", "っ"),
-        ScriptTup("xtu", "っ"),
-        ScriptTup("ltsu", "っ"),
-    ],
-
-    punctuation: [
-        ScriptTup("[", "「"),
-        ScriptTup("]", "」"),
-        ScriptTup("*", "＊"),
-        ScriptTup("?", "？"),
-        ScriptTup(".", "。"),
-        ScriptTup(",", "、"),
-    ],
-};
+    // loanwords are kept apart, they shadow some of the other spellings ("ti" → ち)
+    fn loanwords_to_hiragana(&self, buf: &mut BufWriter<File>) {
+        let data = self.gen(self.loanwords.clone(), false);
+
+        self.write("LOANWORDS_TO_HIRAGANA", buf, &data)
+    }
+}
 
 // NOTE:
-// This could really just be a constant containing the vector of tuples
+// This could really just be a vector of tuples
 // But making this a struct allows us to "inherit" the Map and Gen traits
 struct Kana<'a> {
-    data: [ScriptTup<'a>; 90],
+    // Direct mappings from hiragana to katakana char-by-char
+    data: Vec<ScriptTup<'a>>,
 }
 
-// Direct mappings from hiragana to katakana char-by-char
-const KANA: Kana = Kana {
-    data: [
-        ScriptTup("ぁ", "ァ"),
-        ScriptTup("あ", "ア"),
-        ScriptTup("ぃ", "ィ"),
-        ScriptTup("い", "イ"),
-        ScriptTup("ぅ", "ゥ"),
-        ScriptTup("う", "ウ"),
-        ScriptTup("ぇ", "ェ"),
-        ScriptTup("え", "エ"),
-        ScriptTup("ぉ", "ォ"),
-        ScriptTup("お", "オ"),
-        ScriptTup("か", "カ"),
-        ScriptTup("が", "ガ"),
-        ScriptTup("き", "キ"),
-        ScriptTup("ぎ", "ギ"),
-        ScriptTup("く", "ク"),
-        ScriptTup("ぐ", "グ"),
-        ScriptTup("け", "ケ"),
-        ScriptTup("げ", "ゲ"),
-        ScriptTup("こ", "コ"),
-        ScriptTup("ご", "ゴ"),
-        ScriptTup("さ", "サ"),
-        ScriptTup("ざ", "ザ"),
-        ScriptTup("し", "シ"),
-        ScriptTup("じ", "ジ"),
-        ScriptTup("す", "ス"),
-        ScriptTup("ず", "ズ"),
-        ScriptTup("せ", "セ"),
-        ScriptTup("ぜ", "ゼ"),
-        ScriptTup("そ", "ソ"),
-        ScriptTup("ぞ", "ゾ"),
-        ScriptTup("た", "タ"),
-        ScriptTup("だ", "ダ"),
-        ScriptTup("ち", "チ"),
-        ScriptTup("ぢ", "ヂ"),
-        ScriptTup("っ", "ッ"),
-        ScriptTup("つ", "ツ"),
-        ScriptTup("づ", "ヅ"),
-        ScriptTup("て", "テ"),
-        ScriptTup("で", "デ"),
-        ScriptTup("と", "ト"),
-        ScriptTup("ど", "ド"),
-        ScriptTup("な", "ナ"),
-        ScriptTup("に", "ニ"),
-        ScriptTup("ぬ", "ヌ"),
-        ScriptTup("ね", "ネ"),
-        ScriptTup("の", "ノ"),
-        ScriptTup("は", "ハ"),
-        ScriptTup("ば", "バ"),
-        ScriptTup("ぱ", "パ"),
-        ScriptTup("ひ", "ヒ"),
-        ScriptTup("び", "ビ"),
-        ScriptTup("ぴ", "ピ"),
-        ScriptTup("ふ", "フ"),
-        ScriptTup("ぶ", "ブ"),
-        ScriptTup("ぷ", "プ"),
-        ScriptTup("へ", "ヘ"),
-        ScriptTup("べ", "ベ"),
-        ScriptTup("ぺ", "ペ"),
-        ScriptTup("ほ", "ホ"),
-        ScriptTup("ぼ", "ボ"),
-        ScriptTup("ぽ", "ポ"),
-        ScriptTup("ま", "マ"),
-        ScriptTup("み", "ミ"),
-        ScriptTup("む", "ム"),
-        ScriptTup("め", "メ"),
-        ScriptTup("も", "モ"),
-        ScriptTup("ゃ", "ャ"),
-        ScriptTup("や", "ヤ"),
-        ScriptTup("ゅ", "ュ"),
-        ScriptTup("ゆ", "ユ"),
-        ScriptTup("ょ", "ョ"),
-        ScriptTup("よ", "ヨ"),
-        ScriptTup("ら", "ラ"),
-        ScriptTup("り", "リ"),
-        ScriptTup("る", "ル"),
-        ScriptTup("れ", "レ"),
-        ScriptTup("ろ", "ロ"),
-        ScriptTup("ゎ", "ヮ"),
-        ScriptTup("わ", "ワ"),
-        ScriptTup("ゐ", "ヰ"),
-        ScriptTup("ゑ", "ヱ"),
-        ScriptTup("を", "ヲ"),
-        ScriptTup("ん", "ン"),
-        ScriptTup("ゔ", "ヴ"),
-        ScriptTup("ゕ", "ヵ"),
-        ScriptTup("ゖ", "ヶ"),
-        ScriptTup("わ゛", "ヷ"),
-        ScriptTup("ゐ゛", "ヸ"),
-        ScriptTup("ゑ゛", "ヹ"),
-        ScriptTup("を゛", "ヺ"),
-    ],
-};
-
 impl Map<'_> for Kana<'_> {}
 
 impl Gen<'_> for Kana<'_> {}
 
 impl<'a> Kana<'a> {
-    fn hiragana_to_katakana(self, buf: &mut BufWriter<File>) {
-        let data = self.gen(self.data.to_vec(), false);
+    fn load() -> Self {
+        Kana {
+            data: load("kana.tsv"),
+        }
+    }
+
+    fn hiragana_to_katakana(&self, buf: &mut BufWriter<File>) {
+        let data = self.gen(self.data.clone(), false);
 
         self.write("HIRAGANA_TO_KATAKANA", buf, &data)
     }
 
-    fn katakana_to_hiragana(self, buf: &mut BufWriter<File>) {
-        let data = self.gen(self.data.to_vec(), true);
+    fn katakana_to_hiragana(&self, buf: &mut BufWriter<File>) {
+        let data = self.gen(self.data.clone(), true);
 
         self.write("KATAKANA_TO_HIRAGANA", buf, &data)
     }
+
+    // The table is in gojūon order, which the phf maps don't keep
+    fn gojuon(&self, buf: &mut BufWriter<File>) {
+        let hiragana: Vec<String> = self
+            .data
+            .iter()
+            .map(|ScriptTup(hiragana, _, _)| format!("{:?}", hiragana))
+            .collect();
+
+        writeln!(
+            buf,
+            "pub static GOJUON: &[&str] = &[{}];\n",
+            hiragana.join(", ")
+        )
+        .unwrap()
+    }
+}
+
+// Hentaigana and archaic kana, read as the modern kana they stand for.
+// Only ever read, so there's no inverse.
+struct Hentaigana<'a> {
+    data: Vec<ScriptTup<'a>>,
+}
+
+impl Map<'_> for Hentaigana<'_> {}
+
+impl Gen<'_> for Hentaigana<'_> {}
+
+impl<'a> Hentaigana<'a> {
+    fn load() -> Self {
+        Hentaigana {
+            data: load("hentaigana.tsv"),
+        }
+    }
+
+    fn hentaigana_to_kana(&self, buf: &mut BufWriter<File>) {
+        let data = self.gen(self.data.clone(), false);
+
+        self.write("HENTAIGANA_TO_KANA", buf, &data)
+    }
+}
+
+// Ainu syllable-final consonants as small katakana, an optional set layered
+// over the other tables at runtime. Keys are the consonant, or the vowel before
+// it and the consonant where the kana depends on the vowel (ㇻ is "ar").
+struct Ainu<'a> {
+    finals: Vec<ScriptTup<'a>>,
+}
+
+impl Map<'_> for Ainu<'_> {}
+
+impl Gen<'_> for Ainu<'_> {}
+
+impl<'a> Ainu<'a> {
+    fn load() -> Self {
+        Ainu {
+            finals: load("ainu.tsv"),
+        }
+    }
+
+    fn romaji_to_katakana(&self, buf: &mut BufWriter<File>) {
+        let data = self.gen(self.finals.clone(), false);
+
+        self.write("AINU_TO_KATAKANA", buf, &data)
+    }
+
+    // Only the consonant is written, the vowel before it is already there. The
+    // glides are full-size kana, which are romanized as vowels.
+    fn katakana_to_romaji(&self, buf: &mut BufWriter<File>) {
+        let finals = self
+            .finals
+            .iter()
+            .filter(|ScriptTup(_, value, _)| value.starts_with(is_small_katakana))
+            .map(|&ScriptTup(key, value, origin)| ScriptTup(&key[key.len() - 1..], value, origin))
+            .collect();
+        let data = self.gen(finals, true);
+
+        self.write("KATAKANA_TO_AINU", buf, &data)
+    }
+}
+
+// Physical keys of the JIS X 6002 kana layout, keyed by the unshifted ASCII
+// printed on a JIS keyboard. ¥ is the yen key and \ is the ろ key next to right shift.
+struct JisKana<'a> {
+    unshifted: Vec<ScriptTup<'a>>,
+    shifted: Vec<ScriptTup<'a>>,
 }
 
-// Generate all permutations of necessary data mappings at compile-time.
+impl Map<'_> for JisKana<'_> {}
+
+impl Gen<'_> for JisKana<'_> {}
+
+impl<'a> JisKana<'a> {
+    fn load() -> Self {
+        JisKana {
+            unshifted: load("jis/unshifted.tsv"),
+            shifted: load("jis/shifted.tsv"),
+        }
+    }
+
+    fn keys_to_hiragana(&self, buf: &mut BufWriter<File>) {
+        let data = self.gen(self.unshifted.clone(), false);
+
+        self.write("JIS_TO_HIRAGANA", buf, &data)
+    }
+
+    fn shifted_keys_to_hiragana(&self, buf: &mut BufWriter<File>) {
+        let data = self.gen(self.shifted.clone(), false);
+
+        self.write("JIS_SHIFTED_TO_HIRAGANA", buf, &data)
+    }
+}
+
+// Generate all permutations of the data/ mappings at compile-time.
 // This avoids the use of lazy_static! and hand-writing repetitive data in a file.
 // The disadvantage is that the actual data is not easily debuggable/visible to humans.
 // However, tests in the source code can solve the problem of correctness (you'd need those anyway).
@@ -585,10 +362,288 @@ fn main() {
     let path = Path::new(&env::var("OUT_DIR").unwrap()).join("data.rs");
     let mut file = BufWriter::new(File::create(&path).unwrap());
 
-    GEMINATES.romaji_to_partial_hiragana(&mut file);
-    GEMINATES.partial_hiragana_to_romaji(&mut file);
-    ROMAJI.romaji_to_hiragana(&mut file);
-    ROMAJI.hiragana_to_romaji(&mut file);
-    KANA.katakana_to_hiragana(&mut file);
-    KANA.hiragana_to_katakana(&mut file);
+    let geminates = Geminates::load();
+    let romaji = Romaji::load();
+    let kana = Kana::load();
+    let jis_kana = JisKana::load();
+    let ainu = Ainu::load();
+    let hentaigana = Hentaigana::load();
+
+    validate(&geminates, &romaji, &kana, &jis_kana, &ainu, &hentaigana);
+
+    geminates.romaji_to_partial_hiragana(&mut file);
+    geminates.partial_hiragana_to_romaji(&mut file);
+    romaji.romaji_to_hiragana(&mut file);
+    romaji.hiragana_to_romaji(&mut file);
+    romaji.loanwords_to_hiragana(&mut file);
+    kana.katakana_to_hiragana(&mut file);
+    kana.hiragana_to_katakana(&mut file);
+    kana.gojuon(&mut file);
+    jis_kana.keys_to_hiragana(&mut file);
+    jis_kana.shifted_keys_to_hiragana(&mut file);
+    ainu.romaji_to_katakana(&mut file);
+    ainu.katakana_to_romaji(&mut file);
+    hentaigana.hentaigana_to_kana(&mut file);
+
+    #[cfg(feature = "ffi")]
+    generate_header();
+}
+
+// Check the data/ tables before generating anything, and fail the build with
+// every problem found:
+//
+// 1. Duplicate keys, which phf can't build
+// 2. Inversion collisions, where two pairs would write the same kana (or katakana)
+// 3. Unreachable pairs, which conversion can never match
+// 4. Coverage gaps, kana missing from kana.tsv or without a romaji spelling
+fn validate(
+    geminates: &Geminates<'static>,
+    romaji: &Romaji<'static>,
+    kana: &Kana<'static>,
+    jis_kana: &JisKana<'static>,
+    ainu: &Ainu<'static>,
+    hentaigana: &Hentaigana<'static>,
+) {
+    let mut problems = Vec::new();
+
+    let concat = |groups: &[&Vec<ScriptTup<'static>>]| -> Vec<ScriptTup<'static>> {
+        groups
+            .iter()
+            .flat_map(|group| group.iter().cloned())
+            .collect()
+    };
+
+    let romaji_read = concat(&[
+        &romaji.dominant,
+        &romaji.rest,
+        &romaji.with_sokuon,
+        &romaji.punctuation,
+    ]);
+    let romaji_written = concat(&[&romaji.dominant, &romaji.punctuation]);
+    let geminates_read = concat(&[&geminates.dominant, &geminates.rest]);
+
+    duplicates(&romaji_read, false, &mut problems);
+    duplicates(&romaji_written, true, &mut problems);
+    duplicates(&geminates_read, false, &mut problems);
+    duplicates(&geminates.dominant, true, &mut problems);
+    duplicates(&kana.data, false, &mut problems);
+    duplicates(&kana.data, true, &mut problems);
+    duplicates(&jis_kana.unshifted, false, &mut problems);
+    duplicates(&jis_kana.shifted, false, &mut problems);
+    duplicates(&romaji.unwritten, false, &mut problems);
+    duplicates(&romaji.loanwords, false, &mut problems);
+    duplicates(&romaji.loanwords, true, &mut problems);
+    duplicates(&ainu.finals, false, &mut problems);
+    duplicates(&ainu.finals, true, &mut problems);
+    duplicates(&hentaigana.data, false, &mut problems);
+
+    // Input is lowercased before lookup
+    for ScriptTup(key, _, origin) in romaji_read
+        .iter()
+        .chain(romaji.loanwords.iter())
+        .chain(geminates_read.iter())
+    {
+        if key.to_lowercase() != *key {
+            problems.push(format!(
+                "{}: {:?} is unreachable, romaji is lowercased before conversion",
+                origin, key
+            ));
+        }
+    }
+
+    // A geminate only applies when the rest of it starts a romaji key
+    for ScriptTup(key, partial, origin) in geminates_read.iter() {
+        let mut chars = partial.chars();
+        let rest: String = chars.clone().skip(1).collect();
+
+        if !matches!(chars.next(), Some('っ') | Some('ん')) || rest.is_empty() {
+            problems.push(format!(
+                "{}: {:?} must map to っ or ん followed by romaji, found {:?}",
+                origin, key, partial
+            ));
+        } else if !romaji_read
+            .iter()
+            .any(|ScriptTup(romaji, _, _)| romaji.starts_with(&rest))
+        {
+            problems.push(format!(
+                "{}: {:?} is unreachable, no romaji starts with {:?}",
+                origin, key, rest
+            ));
+        }
+    }
+
+    // An Ainu final is a consonant, after a vowel at most
+    for ScriptTup(key, _, origin) in ainu.finals.iter() {
+        let chars: Vec<char> = key.chars().collect();
+
+        let valid = match chars[..] {
+            [consonant] => is_consonant(consonant),
+            [vowel, consonant] => "aiueo".contains(vowel) && is_consonant(consonant),
+            _ => false,
+        };
+        if !valid {
+            problems.push(format!(
+                "{}: {:?} must be a consonant, or a vowel and a consonant",
+                origin, key
+            ));
+        }
+    }
+
+    // Hentaigana are single chars of the Kana Supplement to Small Kana Extension blocks
+    for ScriptTup(key, _, origin) in hentaigana.data.iter() {
+        let mut chars = key.chars();
+
+        match (chars.next(), chars.next()) {
+            (Some(ch), None) if ('\u{1B000}'..='\u{1B16F}').contains(&ch) => {}
+            _ => problems.push(format!(
+                "{}: {:?} must be a single char from U+1B000 to U+1B16F",
+                origin, key
+            )),
+        }
+    }
+
+    // JIS keys are single keystrokes
+    for ScriptTup(key, _, origin) in jis_kana.unshifted.iter().chain(jis_kana.shifted.iter()) {
+        if key.chars().count() != 1 {
+            problems.push(format!(
+                "{}: {:?} is unreachable, keys are a single character",
+                origin, key
+            ));
+        }
+    }
+
+    let to_hiragana: HashMap<char, &str> = kana
+        .data
+        .iter()
+        .filter_map(|ScriptTup(hiragana, katakana, _)| {
+            let mut chars = katakana.chars();
+            match (chars.next(), chars.next()) {
+                (Some(ch), None) => Some((ch, *hiragana)),
+                _ => None,
+            }
+        })
+        .collect();
+    let known: HashSet<char> = kana
+        .data
+        .iter()
+        .flat_map(|ScriptTup(hiragana, katakana, _)| hiragana.chars().chain(katakana.chars()))
+        .collect();
+
+    // Every kana that can be produced needs a hiragana/katakana pair
+    let produced = concat(&[
+        &romaji.dominant,
+        &romaji.rest,
+        &romaji.with_sokuon,
+        &romaji.loanwords,
+        &geminates_read,
+        &jis_kana.unshifted,
+        &jis_kana.shifted,
+        &hentaigana.data,
+    ]);
+
+    for ScriptTup(_, value, origin) in produced.iter() {
+        for ch in value
+            .chars()
+            .filter(|ch| is_kana_letter(*ch) && !known.contains(ch))
+        {
+            problems.push(format!(
+                "{}: {:?} has no hiragana/katakana pair in data/kana.tsv",
+                origin, ch
+            ));
+        }
+    }
+
+    // Every kana needs a romaji spelling, unless it's listed as unwritten
+    let written: HashSet<String> = romaji
+        .dominant
+        .iter()
+        .map(|ScriptTup(_, value, _)| {
+            value
+                .chars()
+                .map(|ch| {
+                    to_hiragana
+                        .get(&ch)
+                        .map_or(ch.to_string(), |h| h.to_string())
+                })
+                .collect()
+        })
+        .collect();
+    let unwritten: HashMap<&str, Origin> = romaji
+        .unwritten
+        .iter()
+        .map(|ScriptTup(hiragana, _, origin)| (*hiragana, *origin))
+        .collect();
+
+    for ScriptTup(hiragana, katakana, origin) in kana.data.iter() {
+        match (written.contains(*hiragana), unwritten.get(hiragana)) {
+            (false, None) => problems.push(format!(
+                "{}: {:?} ({}) has no spelling in data/romaji/dominant.tsv, add one or list it in data/romaji/unwritten.tsv",
+                origin, hiragana, katakana
+            )),
+            (true, Some(origin)) => problems.push(format!(
+                "{}: {:?} is listed as unwritten but has a spelling in data/romaji/dominant.tsv",
+                origin, hiragana
+            )),
+            _ => {}
+        }
+    }
+
+    if !problems.is_empty() {
+        panic!(
+            "\n\n{} problem(s) in the conversion data:\n\n{}\n\n",
+            problems.len(),
+            problems.join("\n")
+        );
+    }
+}
+
+// Keys that appear twice, or values when the table is inverted
+fn duplicates(data: &[ScriptTup<'static>], invert: bool, problems: &mut Vec<String>) {
+    let mut seen: HashMap<&str, Origin> = HashMap::new();
+
+    for &ScriptTup(key, value, origin) in data.iter() {
+        let key = if invert { value } else { key };
+
+        match seen.get(key) {
+            Some(first) if invert => problems.push(format!(
+                "{}: {:?} is already written by {}, inverting would keep only one",
+                origin, key, first
+            )),
+            Some(first) => problems.push(format!(
+                "{}: duplicate key {:?}, first defined at {}",
+                origin, key, first
+            )),
+            None => {
+                seen.insert(key, origin);
+            }
+        }
+    }
+}
+
+// The Katakana Phonetic Extensions block
+fn is_small_katakana(ch: char) -> bool {
+    ('\u{31F0}'..='\u{31FF}').contains(&ch)
+}
+
+fn is_consonant(ch: char) -> bool {
+    ch.is_ascii_lowercase() && !"aiueo".contains(ch)
+}
+
+// Hiragana and katakana letters, leaving out marks like ー, ・ and ゛
+fn is_kana_letter(ch: char) -> bool {
+    ('\u{3041}'..='\u{3096}').contains(&ch) || ('\u{30A1}'..='\u{30FA}').contains(&ch)
+}
+
+// Generate konj.h from src/ffi.rs, configured by cbindgen.toml, into OUT_DIR.
+// tests/ffi.rs checks the copy in include/ against it.
+#[cfg(feature = "ffi")]
+fn generate_header() {
+    let crate_dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+    let header = Path::new(&env::var("OUT_DIR").unwrap()).join("konj.h");
+
+    cbindgen::generate(&crate_dir)
+        .expect("Unable to generate C header")
+        .write_to_file(&header);
+
+    println!("cargo:rustc-env=KONJ_HEADER={}", header.display());
 }
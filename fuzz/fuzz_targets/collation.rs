@@ -0,0 +1,16 @@
+#![no_main]
+
+use konj::collation::{cmp, sort_key};
+use konj_fuzz::text;
+use libfuzzer_sys::fuzz_target;
+
+// The input is two strings, split at the first tab
+fuzz_target!(|data: &[u8]| {
+    let input = text(data);
+    let (a, b) = input.split_once('\t').unwrap_or((&input, ""));
+
+    sort_key(a);
+
+    assert_eq!(cmp(a, a), std::cmp::Ordering::Equal);
+    assert_eq!(cmp(a, b), cmp(b, a).reverse());
+});
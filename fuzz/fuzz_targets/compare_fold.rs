@@ -0,0 +1,22 @@
+#![no_main]
+
+use konj::compare::{eq, fold, FoldOptions};
+use konj_fuzz::{check_growth, text};
+use libfuzzer_sys::fuzz_target;
+
+// The first byte picks the fold options, the rest is the input
+fuzz_target!(|data: &[u8]| {
+    let (options, input) = match data.split_first() {
+        Some((options, input)) => (*options, text(input)),
+        None => return,
+    };
+
+    let options = FoldOptions {
+        ignore_small_kana: options & 1 != 0,
+        ignore_voicing: options & 2 != 0,
+        ignore_long_vowels: options & 4 != 0,
+    };
+
+    check_growth(&input, &fold(&input, options));
+    assert!(eq(&input, &input, options));
+});
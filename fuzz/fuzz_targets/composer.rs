@@ -0,0 +1,28 @@
+#![no_main]
+
+use konj::composer::RomajiComposer;
+use konj_fuzz::{check_growth, text};
+use libfuzzer_sys::fuzz_target;
+
+// The input is typed one key at a time, with "\u{8}" as backspace
+fuzz_target!(|data: &[u8]| {
+    let input = text(data);
+    let mut composer = RomajiComposer::new();
+
+    for key in input.chars() {
+        if key == '\u{8}' {
+            composer.backspace();
+        } else {
+            composer.push(key);
+        }
+
+        assert_eq!(
+            composer.display(),
+            format!("{}{}", composer.committed(), composer.pending())
+        );
+    }
+
+    let typed = composer.finish();
+    check_growth(&input, &typed);
+    assert!(composer.committed().is_empty() && composer.pending().is_empty());
+});
@@ -0,0 +1,108 @@
+#![no_main]
+
+use konj::converter::{
+    Case, Converter, DanglingSokuon, Loanwords, LongVowel, Orthography, Particles, Punctuation,
+    Romanization, Target, Unknown,
+};
+use konj::error::Error;
+use konj::tables::Tables;
+use konj_fuzz::{check_growth, text};
+use libfuzzer_sys::fuzz_target;
+
+// The first four bytes pick the converter options and entry point, the rest is the input
+fuzz_target!(|data: &[u8]| {
+    if data.len() < 4 {
+        return;
+    }
+
+    let input = text(&data[4..]);
+    let mut options = u32::from_le_bytes([data[0], data[1], data[2], data[3]]) as usize;
+    let mut pick = |count: usize| {
+        let index = options % count;
+        options /= count;
+        index
+    };
+
+    let mut tables = Tables::builtin();
+    if pick(2) == 1 {
+        tables.add_ainu();
+    }
+
+    let converter = Converter::builder()
+        .target([Target::Hiragana, Target::Katakana, Target::Romaji][pick(3)])
+        .romanization(
+            [
+                Romanization::Hepburn,
+                Romanization::Kunrei,
+                Romanization::Nihon,
+            ][pick(3)],
+        )
+        .punctuation([Punctuation::Convert, Punctuation::Keep][pick(2)])
+        .unknown([Unknown::Keep, Unknown::Drop, Unknown::Replace('?')][pick(3)])
+        .case([Case::Lower, Case::Upper, Case::Capitalized][pick(3)])
+        .long_vowel([LongVowel::Spelled, LongVowel::Macron, LongVowel::Circumflex][pick(3)])
+        .dangling_sokuon(
+            [
+                DanglingSokuon::Apostrophe,
+                DanglingSokuon::H,
+                DanglingSokuon::Omit,
+                DanglingSokuon::Unknown,
+            ][pick(4)],
+        )
+        .loanwords([Loanwords::Ime, Loanwords::Guideline][pick(2)])
+        .orthography([Orthography::Modern, Orthography::Historical][pick(2)])
+        .particles([Particles::Spelling, Particles::Pronunciation][pick(2)])
+        .tables(tables)
+        .build();
+
+    let chars: Vec<char> = input.chars().collect();
+
+    match pick(4) {
+        0 => check_growth(&input, &converter.convert(&input)),
+
+        // Fails exactly when convert_lenient warns, with the same fragments
+        1 => match converter.try_convert(&input) {
+            Ok(output) => assert_eq!(output, converter.convert(&input)),
+            Err(Error::Unconvertible(fragments)) => {
+                assert_eq!(fragments, converter.convert_lenient(&input).warnings)
+            }
+            Err(error) => panic!("unexpected error {:?}", error),
+        },
+
+        // Warnings are non-empty input fragments, in order and apart from each other
+        2 => {
+            let lenient = converter.convert_lenient(&input);
+            let mut end = 0;
+
+            assert_eq!(lenient.text, converter.convert(&input));
+
+            for fragment in lenient.warnings.iter() {
+                assert!(fragment.input.start >= end && fragment.input.start < fragment.input.end);
+                assert!(fragment.input.end <= chars.len());
+                assert_eq!(
+                    fragment.text,
+                    chars[fragment.input.clone()].iter().collect::<String>()
+                );
+                end = fragment.input.end + 1;
+            }
+        }
+
+        // Spans cover the input and the output without gaps
+        _ => {
+            let aligned = converter.convert_aligned(&input);
+            let (mut input_end, mut output_end) = (0, 0);
+
+            check_growth(&input, &aligned.text);
+
+            for span in aligned.spans.iter() {
+                assert_eq!(span.input.start, input_end);
+                assert_eq!(span.output.start, output_end);
+                input_end = span.input.end;
+                output_end = span.output.end;
+            }
+
+            assert_eq!(input_end, chars.len());
+            assert_eq!(output_end, aligned.text.chars().count());
+        }
+    }
+});
@@ -0,0 +1,14 @@
+#![no_main]
+
+use konj::kana::from_hentaigana;
+use konj_fuzz::{check_growth, text};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let input = text(data);
+    let kana = from_hentaigana(&input);
+
+    // Hentaigana become standard kana, so a second pass changes nothing
+    check_growth(&input, &kana);
+    assert_eq!(from_hentaigana(&kana), kana);
+});
@@ -0,0 +1,15 @@
+#![no_main]
+
+use konj::jis::{parse_keys, to_hiragana, to_katakana, to_romaji};
+use konj_fuzz::{check_growth, text};
+use libfuzzer_sys::fuzz_target;
+
+// The input is read as a keystroke log
+fuzz_target!(|data: &[u8]| {
+    let input = text(data);
+    let keys = parse_keys(&input);
+
+    check_growth(&input, &to_hiragana(&keys));
+    check_growth(&input, &to_katakana(&keys));
+    check_growth(&input, &to_romaji(&keys));
+});
@@ -0,0 +1,11 @@
+#![no_main]
+
+use konj::kana::normalize;
+use konj_fuzz::{check_growth, text};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let input = text(data);
+
+    check_growth(&input, &normalize(&input));
+});
@@ -0,0 +1,25 @@
+#![no_main]
+
+use konj::lexer::{lex, parse_str};
+use konj_fuzz::text;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let input = text(data);
+    let groups = lex(&input).unwrap();
+
+    // Groups cover the input exactly, each in a single lexical class
+    assert_eq!(groups.concat(), input);
+
+    let mut items = parse_str(&input).into_iter();
+
+    for group in groups.iter() {
+        let mut group_items = items.by_ref().take(group.chars().count());
+        let first = group_items.next().expect("empty group");
+        assert!(
+            group_items.all(|item| item == first),
+            "mixed group {:?}",
+            group
+        );
+    }
+});
@@ -0,0 +1,28 @@
+#![no_main]
+
+use konj::mora::segment;
+use konj_fuzz::text;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let input = text(data);
+    let chars: Vec<char> = input.chars().map(|ch| ch.to_ascii_lowercase()).collect();
+    let mut end = 0;
+
+    // Morae are the input in order, skipping only whitespace
+    for mora in segment(&input) {
+        let len = mora.romaji.chars().count();
+
+        assert!(len > 0, "empty mora at {}", mora.offset);
+        assert!(chars[end..mora.offset].iter().all(|ch| ch.is_whitespace()));
+        assert_eq!(
+            mora.romaji,
+            chars[mora.offset..mora.offset + len]
+                .iter()
+                .collect::<String>()
+        );
+        end = mora.offset + len;
+    }
+
+    assert!(chars[end..].iter().all(|ch| ch.is_whitespace()));
+});
@@ -0,0 +1,27 @@
+#![no_main]
+
+use konj::strings::repeatedly_replace_str_with_map;
+use konj::{
+    GEMINATES_TO_HIRAGANA, HIRAGANA_TO_KATAKANA, HIRAGANA_TO_ROMAJI, KATAKANA_TO_HIRAGANA,
+    ROMAJI_TO_HIRAGANA,
+};
+use konj_fuzz::{check_growth, text};
+use libfuzzer_sys::fuzz_target;
+
+// The first byte picks the map, the rest is the input
+fuzz_target!(|data: &[u8]| {
+    let (map, input) = match data.split_first() {
+        Some((map, input)) => (*map as usize, text(input)),
+        None => return,
+    };
+
+    let map = [
+        &HIRAGANA_TO_KATAKANA,
+        &KATAKANA_TO_HIRAGANA,
+        &HIRAGANA_TO_ROMAJI,
+        &ROMAJI_TO_HIRAGANA,
+        &GEMINATES_TO_HIRAGANA,
+    ][map % 5];
+
+    check_growth(&input, &repeatedly_replace_str_with_map(&input, map));
+});
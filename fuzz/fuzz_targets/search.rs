@@ -0,0 +1,26 @@
+#![no_main]
+
+use konj::search::{find, find_prefix, starts_with, Match};
+use konj_fuzz::text;
+use libfuzzer_sys::fuzz_target;
+
+// The input is the query and the haystack, split at the first tab
+fuzz_target!(|data: &[u8]| {
+    let input = text(data);
+    let (query, haystack) = input.split_once('\t').unwrap_or((&input, &input));
+    let len = haystack.chars().count();
+
+    let check = |found: Option<Match>| {
+        if let Some(found) = found {
+            assert!(found.start <= found.end && found.end <= len, "{:?}", found);
+        }
+    };
+
+    check(find(query, haystack));
+    check(find_prefix(query, haystack));
+
+    // A match at the start is also a match anywhere
+    if starts_with(query, haystack) {
+        assert!(find_prefix(query, haystack).is_some());
+    }
+});
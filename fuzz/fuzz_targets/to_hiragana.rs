@@ -0,0 +1,14 @@
+#![no_main]
+
+use konj_fuzz::{check_growth, text};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let input = text(data);
+
+    check_growth(&input, &konj::to_hiragana(&input));
+
+    if let Ok(output) = konj::try_to_hiragana(&input) {
+        check_growth(&input, &output);
+    }
+});
@@ -0,0 +1,18 @@
+#![no_main]
+
+use konj::verse::{check, Form};
+use konj_fuzz::text;
+use libfuzzer_sys::fuzz_target;
+
+// The first byte picks the form, the rest is the poem
+fuzz_target!(|data: &[u8]| {
+    let (form, input) = match data.split_first() {
+        Some((form, input)) => ([Form::Haiku, Form::Tanka][*form as usize % 2], text(input)),
+        None => return,
+    };
+
+    let verse = check(&input, form);
+
+    assert!(verse.counts.len() <= input.split(['\n', '/']).count());
+    assert_eq!(verse.is_valid(), verse.mismatched_lines().is_empty());
+});
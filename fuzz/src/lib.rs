@@ -0,0 +1,22 @@
+// Checks shared by the fuzz targets
+use std::borrow::Cow;
+
+// Fuzz input as text. Invalid UTF-8 is replaced with U+FFFD rather than
+// skipped, so malformed bytes still reach the converters.
+pub fn text(data: &[u8]) -> Cow<'_, str> {
+    String::from_utf8_lossy(data)
+}
+
+// Every conversion maps a char to a handful of chars at most (あ → "a",
+// ā → "ああ"), or fails with the fixed error message
+pub fn check_growth(input: &str, output: &str) {
+    let limit = 4 * input.len() + 64;
+
+    assert!(
+        output.len() <= limit,
+        "output grew from {} to {} bytes for {:?}",
+        input.len(),
+        output.len(),
+        input
+    );
+}
@@ -0,0 +1,84 @@
+#ifndef KONJ_H
+#define KONJ_H
+
+/* Generated by cbindgen from src/ffi.rs. Do not edit by hand. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+typedef enum {
+  KONJ_LEX_ITEM_ROM,
+  KONJ_LEX_ITEM_NUM,
+  KONJ_LEX_ITEM_KANJI,
+  KONJ_LEX_ITEM_HIRAGANA,
+  KONJ_LEX_ITEM_KATAKANA,
+  KONJ_LEX_ITEM_SPACE,
+  KONJ_LEX_ITEM_PUNCT,
+  KONJ_LEX_ITEM_OTHER,
+} KonjLexItem;
+
+typedef enum {
+  KONJ_STATUS_OK = 0,
+  KONJ_STATUS_NULL_POINTER = 1,
+  KONJ_STATUS_INVALID_UTF8 = 2,
+  KONJ_STATUS_UNKNOWN_SCRIPT = 3,
+  KONJ_STATUS_INVALID_TABLE = 4,
+  KONJ_STATUS_UNCONVERTIBLE = 5,
+} KonjStatus;
+
+typedef struct {
+  uintptr_t total;
+  uintptr_t counts[8];
+  double percentages[8];
+  KonjLexItem dominant;
+  bool is_neutral;
+  bool is_mixed;
+} KonjScript;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * # Safety
+ *
+ * `input` must be NULL or a NUL-terminated string, `output` must be NULL or valid for writes.
+ */
+KonjStatus konj_to_hiragana(const char *input, char **output);
+
+/**
+ * # Safety
+ *
+ * `input` must be NULL or a NUL-terminated string, `output` must be NULL or valid for writes.
+ */
+KonjStatus konj_to_katakana(const char *input, char **output);
+
+/**
+ * # Safety
+ *
+ * `input` must be NULL or a NUL-terminated string, `output` must be NULL or valid for writes.
+ */
+KonjStatus konj_to_romaji(const char *input, char **output);
+
+/**
+ * # Safety
+ *
+ * `input` must be NULL or a NUL-terminated string, `output` must be NULL or valid for writes.
+ */
+KonjStatus konj_detect_script(const char *input, KonjScript *output);
+
+/**
+ * # Safety
+ *
+ * `string` must be NULL or a pointer returned by one of the konj_to_* functions,
+ * and must not be used after this call.
+ */
+void konj_string_free(char *string);
+
+#ifdef __cplusplus
+} // extern "C"
+#endif // __cplusplus
+
+#endif /* KONJ_H */
@@ -0,0 +1,169 @@
+use crate::kana::{self, CHOONPU};
+use crate::GOJUON;
+use std::cmp::Ordering;
+
+// A sort key for gojūon (dictionary) order, following JIS X 4061.
+//
+// Strings are compared level by level, each level only breaking ties left by the one before:
+//
+// 1. the base kana in あいうえお order, with hiragana and katakana interleaved and ー read as the vowel before it
+// 2. voicing: unvoiced, then dakuten, then handakuten (は < ば < ぱ)
+// 3. size: full-size, then small (や < ゃ)
+// 4. long vowels: the vowel spelled out, then ー (カア < かー)
+// 5. script: hiragana, then katakana (かあ < カア)
+//
+// so かあ < カア < かー.
+//
+// Characters that aren't kana sort by code point, before kana when they come
+// before the hiragana block (ASCII, Latin) and after it otherwise (kanji).
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SortKey {
+    base: Vec<(u8, u32)>,
+    voicing: Vec<u8>,
+    size: Vec<u8>,
+    long: Vec<u8>,
+    script: Vec<u8>,
+}
+
+// A string ordered by its gojūon sort key
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Gojuon<T: AsRef<str>>(pub T);
+
+impl<T: AsRef<str> + Eq> Ord for Gojuon<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        sort_key(self.0.as_ref())
+            .cmp(&sort_key(other.0.as_ref()))
+            .then_with(|| self.0.as_ref().cmp(other.0.as_ref()))
+    }
+}
+
+impl<T: AsRef<str> + Eq> PartialOrd for Gojuon<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+pub fn sort_key(input: &str) -> SortKey {
+    let mut key = SortKey {
+        base: Vec::new(),
+        voicing: Vec::new(),
+        size: Vec::new(),
+        long: Vec::new(),
+        script: Vec::new(),
+    };
+    let mut vowel = None;
+
+    for ch in input.chars() {
+        if ch == CHOONPU {
+            if let Some(base) = vowel.and_then(kana::vowel_kana).and_then(gojuon_index) {
+                key.push((1, base), 0, 0, 1, 0);
+                continue;
+            }
+        }
+
+        let hiragana = kana::hiragana_char(ch);
+        let large = kana::to_large(hiragana);
+        let base = kana::unvoice(large);
+
+        match gojuon_index(base) {
+            Some(index) => {
+                let voicing = match kana::voice(base) {
+                    _ if base == large => 0,
+                    Some(voiced) if voiced == large => 1,
+                    _ => 2,
+                };
+
+                key.push(
+                    (1, index),
+                    voicing,
+                    (large != hiragana) as u8,
+                    0,
+                    (hiragana != ch) as u8,
+                );
+                vowel = kana::vowel(&hiragana.to_string());
+            }
+
+            None => {
+                let class = if ch < crate::constants::HIRAGANA_BEG {
+                    0
+                } else {
+                    2
+                };
+
+                key.push((class, ch as u32), 0, 0, 0, 0);
+                vowel = None;
+            }
+        }
+    }
+
+    key
+}
+
+pub fn cmp(a: &str, b: &str) -> Ordering {
+    Gojuon(a).cmp(&Gojuon(b))
+}
+
+pub fn sort<T: AsRef<str>>(items: &mut [T]) {
+    items.sort_by(|a, b| cmp(a.as_ref(), b.as_ref()));
+}
+
+impl SortKey {
+    fn push(&mut self, base: (u8, u32), voicing: u8, size: u8, long: u8, script: u8) {
+        self.base.push(base);
+        self.voicing.push(voicing);
+        self.size.push(size);
+        self.long.push(long);
+        self.script.push(script);
+    }
+}
+
+fn gojuon_index(hiragana: char) -> Option<u32> {
+    let hiragana = hiragana.to_string();
+
+    GOJUON
+        .iter()
+        .position(|kana| *kana == hiragana)
+        .map(|index| index as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sort() {
+        let mut words = vec!["ゆき", "さくら", "きゃく", "がき", "カキ", "きやく", "かき"];
+        sort(&mut words);
+        assert_eq!(
+            words,
+            vec!["かき", "カキ", "がき", "きやく", "きゃく", "さくら", "ゆき"]
+        );
+
+        let mut words = vec!["ぱん", "ばん", "はん", "ハン"];
+        sort(&mut words);
+        assert_eq!(words, vec!["はん", "ハン", "ばん", "ぱん"]);
+    }
+
+    #[test]
+    fn test_choonpu() {
+        assert_eq!(cmp("かあ", "かー"), Ordering::Less);
+        assert_eq!(cmp("カア", "かー"), Ordering::Less);
+        assert_eq!(cmp("カード", "かい"), Ordering::Less);
+        assert_eq!(cmp("コーヒー", "こおり"), Ordering::Less);
+        assert_eq!(cmp("コーヒー", "こうちゃ"), Ordering::Greater);
+
+        let mut words = vec!["かー", "カア", "かあ"];
+        sort(&mut words);
+        assert_eq!(words, vec!["かあ", "カア", "かー"]);
+    }
+
+    #[test]
+    fn test_gojuon_ord() {
+        let mut words = vec![Gojuon("漢字"), Gojuon("ん"), Gojuon("abc"), Gojuon("ア")];
+        words.sort();
+        assert_eq!(
+            words,
+            vec![Gojuon("abc"), Gojuon("ア"), Gojuon("ん"), Gojuon("漢字")]
+        );
+    }
+}
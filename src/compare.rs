@@ -0,0 +1,125 @@
+use crate::kana::{self, CHOONPU};
+use std::cmp::Ordering;
+
+// What to ignore when comparing two strings. By default only the script
+// (hiragana, katakana or romaji) is ignored.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FoldOptions {
+    // ゃ matches や, っ matches つ
+    pub ignore_small_kana: bool,
+    // が matches か, ぱ matches は
+    pub ignore_voicing: bool,
+    // ー and vowels that lengthen the one before them (らあ, とう, けい) are dropped
+    pub ignore_long_vowels: bool,
+}
+
+// Fold the input to canonical hiragana so that strings written in different
+// scripts compare equal: "ラーメン", "らーめん" and "raamen" all fold to "らあめん"
+// with the long vowels kept, and to "らめん" with them ignored.
+pub fn fold(input: &str, options: FoldOptions) -> String {
+    let hiragana = kana::normalize(&input.to_ascii_lowercase());
+    let mut result = String::with_capacity(hiragana.len());
+    let mut vowel = None;
+
+    for ch in hiragana.chars() {
+        let mut ch = ch;
+
+        if options.ignore_small_kana {
+            ch = kana::to_large(ch);
+        }
+
+        if options.ignore_voicing {
+            ch = kana::unvoice(ch);
+        }
+
+        let next_vowel = kana::vowel(&ch.to_string());
+
+        if options.ignore_long_vowels {
+            if ch == CHOONPU || is_long_vowel(vowel, ch) {
+                continue;
+            }
+        } else if ch == CHOONPU {
+            // Spell ー out as the vowel it lengthens so it matches romaji
+            if let Some(previous) = vowel.and_then(kana::vowel_kana) {
+                result.push(previous);
+                continue;
+            }
+        }
+
+        if !kana::is_small_glide(ch) {
+            vowel = next_vowel;
+        } else if let Some(glide) = next_vowel {
+            vowel = Some(glide);
+        }
+
+        result.push(ch);
+    }
+
+    result
+}
+
+pub fn eq(a: &str, b: &str, options: FoldOptions) -> bool {
+    fold(a, options) == fold(b, options)
+}
+
+pub fn cmp(a: &str, b: &str, options: FoldOptions) -> Ordering {
+    fold(a, options).cmp(&fold(b, options))
+}
+
+// True when `ch` is a vowel kana that lengthens the preceding `vowel`
+pub(crate) fn is_long_vowel(vowel: Option<char>, ch: char) -> bool {
+    match (vowel, kana::vowel(&ch.to_string())) {
+        (Some(previous), Some(next)) if "あいうえお".contains(ch) => {
+            previous == next || (previous, next) == ('o', 'u') || (previous, next) == ('e', 'i')
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fold() {
+        let options = FoldOptions::default();
+        assert_eq!(fold("ラーメン", options), "らあめん");
+        assert_eq!(fold("らーめん", options), "らあめん");
+        assert_eq!(fold("Raamen", options), "らあめん");
+
+        let options = FoldOptions {
+            ignore_long_vowels: true,
+            ..FoldOptions::default()
+        };
+        assert_eq!(fold("ラーメン", options), "らめん");
+        assert_eq!(fold("ramen", options), "らめん");
+        assert_eq!(fold("とうきょう", options), "ときょ");
+    }
+
+    #[test]
+    fn test_eq() {
+        let options = FoldOptions::default();
+        assert!(eq("ラーメン", "らーめん", options));
+        assert!(eq("ラーメン", "raamen", options));
+        assert!(eq("tōkyō", "とうきょう", options));
+        assert!(eq("rāmen", "ラーメン", options));
+        assert!(!eq("ラーメン", "ramen", options));
+        assert!(!eq("がっこう", "かつこう", options));
+
+        let options = FoldOptions {
+            ignore_small_kana: true,
+            ignore_voicing: true,
+            ignore_long_vowels: true,
+        };
+        assert!(eq("ラーメン", "ramen", options));
+        assert!(eq("がっこう", "かつこ", options));
+    }
+
+    #[test]
+    fn test_cmp() {
+        let options = FoldOptions::default();
+        assert_eq!(cmp("カ", "か", options), Ordering::Equal);
+        assert_eq!(cmp("か", "さ", options), Ordering::Less);
+        assert_eq!(cmp("sa", "カ", options), Ordering::Greater);
+    }
+}
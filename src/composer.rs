@@ -0,0 +1,200 @@
+use crate::{GEMINATES_TO_HIRAGANA, ROMAJI_TO_HIRAGANA};
+
+// IME-style romaji input, one key at a time.
+//
+// Keys are buffered in `pending` until they can no longer grow into a longer
+// romaji key, at which point the kana is moved to `committed`:
+//
+// "k" → "k", "ky" → "ky", "kyo" → "きょ"
+//
+// "nn" and "n'" commit ん, and doubled consonants ("kk", "tt" etc.) commit っ
+// while keeping the second consonant pending.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RomajiComposer {
+    committed: String,
+    pending: String,
+}
+
+impl RomajiComposer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, key: char) {
+        self.pending.push(key.to_ascii_lowercase());
+        self.resolve(false);
+    }
+
+    pub fn push_str(&mut self, keys: &str) {
+        keys.chars().for_each(|key| self.push(key));
+    }
+
+    // Remove the last pending key, or the last committed char when nothing is pending
+    pub fn backspace(&mut self) {
+        if self.pending.pop().is_none() {
+            self.committed.pop();
+        }
+    }
+
+    // Commit whatever is pending as if no more keys are coming, eg. a trailing "n" becomes ん
+    pub fn flush(&mut self) {
+        self.resolve(true);
+    }
+
+    pub fn clear(&mut self) {
+        self.committed.clear();
+        self.pending.clear();
+    }
+
+    pub fn committed(&self) -> &str {
+        &self.committed
+    }
+
+    pub fn pending(&self) -> &str {
+        &self.pending
+    }
+
+    // What an editor shows: committed kana followed by the pending romaji
+    pub fn display(&self) -> String {
+        format!("{}{}", self.committed, self.pending)
+    }
+
+    // Flush and return everything typed so far, leaving the composer empty
+    pub fn finish(&mut self) -> String {
+        self.flush();
+        self.pending.clear();
+        std::mem::take(&mut self.committed)
+    }
+
+    fn resolve(&mut self, is_final: bool) {
+        while !self.pending.is_empty() {
+            if self.pending == "nn" || self.pending == "n'" {
+                self.commit(2, "ん");
+                continue;
+            }
+
+            if !is_final && is_partial_key(&self.pending) {
+                return;
+            }
+
+            if let Some(kana) = ROMAJI_TO_HIRAGANA.get(self.pending.as_str()) {
+                let len = self.pending.chars().count();
+                self.commit(len, kana);
+                continue;
+            }
+
+            if let Some(partial) = geminate(&self.pending) {
+                self.commit(1, partial);
+                continue;
+            }
+
+            match longest_key_prefix(&self.pending) {
+                Some((len, kana)) => self.commit(len, kana),
+                None => {
+                    let literal = self.pending.remove(0);
+                    self.committed.push(literal);
+                }
+            }
+        }
+    }
+
+    // Move the first `len` pending chars over as `kana`
+    fn commit(&mut self, len: usize, kana: &str) {
+        self.committed.push_str(kana);
+        self.pending = self.pending.chars().skip(len).collect();
+    }
+}
+
+// True when `pending` is the start of a longer romaji key
+fn is_partial_key(pending: &str) -> bool {
+    ROMAJI_TO_HIRAGANA
+        .keys()
+        .any(|key| key.len() > pending.len() && key.starts_with(pending))
+}
+
+// っ or ん for the first letter of a geminate, eg. "kk" or "mb"
+fn geminate(pending: &str) -> Option<&'static str> {
+    let mut chars = pending.chars();
+    let (first, second) = (chars.next()?, chars.next()?);
+    let key: String = [first, second].iter().collect();
+
+    if let Some(partial) = GEMINATES_TO_HIRAGANA.get(key.as_str()) {
+        let len = partial.chars().next()?.len_utf8();
+        return Some(&partial[..len]);
+    }
+
+    // Any other doubled consonant ("gg", "dd") is a sokuon as well
+    if first == second && first.is_ascii_alphabetic() && !"aiueon".contains(first) {
+        return Some("っ");
+    }
+
+    None
+}
+
+fn longest_key_prefix(pending: &str) -> Option<(usize, &'static str)> {
+    let chars: Vec<char> = pending.chars().collect();
+
+    (1..chars.len()).rev().find_map(|len| {
+        let key: String = chars[..len].iter().collect();
+        ROMAJI_TO_HIRAGANA
+            .get(key.as_str())
+            .map(|kana| (len, *kana))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn displays(keys: &str) -> Vec<String> {
+        let mut composer = RomajiComposer::new();
+
+        keys.chars()
+            .map(|key| {
+                composer.push(key);
+                composer.display()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_push() {
+        assert_eq!(displays("kyo"), vec!["k", "ky", "きょ"]);
+        assert_eq!(displays("kitte"), vec!["k", "き", "きt", "きっt", "きって"]);
+        assert_eq!(displays("sanpo"), vec!["s", "さ", "さn", "さんp", "さんぽ"]);
+        assert_eq!(
+            displays("kon'ya"),
+            vec!["k", "こ", "こn", "こん", "こんy", "こんや"]
+        );
+        assert_eq!(displays("nna"), vec!["n", "ん", "んあ"]);
+    }
+
+    #[test]
+    fn test_pending_and_committed() {
+        let mut composer = RomajiComposer::new();
+        composer.push_str("shinkan");
+        assert_eq!(composer.committed(), "しんか");
+        assert_eq!(composer.pending(), "n");
+
+        composer.flush();
+        assert_eq!(composer.committed(), "しんかん");
+        assert_eq!(composer.pending(), "");
+
+        composer.push_str("BEDDO.");
+        assert_eq!(composer.finish(), "しんかんべっど。");
+        assert_eq!(composer.display(), "");
+    }
+
+    #[test]
+    fn test_backspace() {
+        let mut composer = RomajiComposer::new();
+        composer.push_str("kaky");
+        composer.backspace();
+        assert_eq!(composer.display(), "かk");
+        composer.backspace();
+        composer.backspace();
+        assert_eq!(composer.display(), "");
+        composer.backspace();
+        assert_eq!(composer.display(), "");
+    }
+}
@@ -7,6 +7,14 @@ pub const HIRAGANA_END: char = '\u{309F}';
 pub const KATAKANA_BEG: char = '\u{30A0}';
 pub const KATAKANA_END: char = '\u{30FF}';
 
+// Katakana Phonetic Extensions, small katakana used for Ainu (ㇰ, ㇻ)
+pub const KATAKANA_EXT_BEG: char = '\u{31F0}';
+pub const KATAKANA_EXT_END: char = '\u{31FF}';
+
+// Kana Supplement, Kana Extended-A and Small Kana Extension: hentaigana and archaic kana
+pub const KANA_SUPPLEMENT_BEG: char = '\u{1B000}';
+pub const KANA_SUPPLEMENT_END: char = '\u{1B16F}';
+
 pub const KANJI_BEG: char = '\u{4E00}';
 pub const KANJI_END: char = '\u{9FAF}';
 
@@ -18,3 +26,17 @@ pub const ROMAN_END: char = '\u{007E}';
 
 pub const JAPANESE_SPACE: char = '　';
 pub const SPACE: char = ' ';
+
+pub const LATIN_UPPER_BEG: char = '\u{0041}';
+pub const LATIN_UPPER_END: char = '\u{005A}';
+
+pub const LATIN_LOWER_BEG: char = '\u{0061}';
+pub const LATIN_LOWER_END: char = '\u{007A}';
+
+pub const CJK_PUNCT_BEG: char = '\u{3001}';
+pub const CJK_PUNCT_END: char = '\u{303F}';
+
+// Fullwidth forms of ASCII; subtract the offset to get the ASCII character
+pub const FULLWIDTH_BEG: char = '\u{FF01}';
+pub const FULLWIDTH_END: char = '\u{FF5E}';
+pub const FULLWIDTH_OFFSET: u32 = 0xFEE0;
@@ -0,0 +1,1346 @@
+use crate::error::Error;
+use crate::kana::{self, CHOONPU, DAKUTEN, HANDAKUTEN, HATSUON, SOKUON};
+use crate::lexer::{parse, LexItem};
+use crate::orthography;
+use crate::strings::is_punctuation;
+use crate::tables::{Table, Tables};
+use crate::LOANWORDS_TO_HIRAGANA;
+use itertools::Itertools;
+use std::ops::Range;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Target {
+    Hiragana,
+    Katakana,
+    Romaji,
+}
+
+// How kana is spelled in romaji output.
+//
+// Hepburn follows the built-in tables (し → shi, ち → chi), Kunrei-shiki and
+// Nihon-shiki follow the kana rows (し → si, ち → ti) and differ on ぢ and づ.
+// Any spelling is accepted as input regardless of this setting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Romanization {
+    Hepburn,
+    Kunrei,
+    Nihon,
+}
+
+// Whether punctuation is converted to the target script ("." ↔ "。")
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Punctuation {
+    Convert,
+    Keep,
+}
+
+// What to do with characters that can't be converted: kanji, letters that
+// aren't romaji, and kana with no romaji spelling
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unknown {
+    Keep,
+    Drop,
+    Replace(char),
+}
+
+// Letter case of romaji output
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Case {
+    Lower,
+    Upper,
+    // The first letter of every word is upper case
+    Capitalized,
+}
+
+// How long vowels (とう, ラー) are written in romaji output. Macron and circumflex
+// vowels in romaji input are read as long vowels regardless of this setting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LongVowel {
+    // toukyou, raamen
+    Spelled,
+    // tōkyō, rāmen
+    Macron,
+    // tôkyô, râmen
+    Circumflex,
+}
+
+// How a っ that can't double the next consonant is written in romaji: at the end
+// of a word (あっ, えっ！), before a vowel (きっう) or before ん
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DanglingSokuon {
+    // A glottal stop: e', ki'u
+    Apostrophe,
+    // e.g. eh, kihu
+    H,
+    // e, kiu
+    Omit,
+    // Left to the unknown character policy, and reported by try_convert
+    Unknown,
+}
+
+// How extended katakana for loanwords (ティ, ファ, トゥ) is read and written
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Loanwords {
+    // As typed into an IME, going by the built-in tables: thi, dhi, twu
+    Ime,
+    // In Hepburn, following the 1991 foreign-word guideline (data/romaji/loanwords.tsv):
+    // ti, di, tu. These spellings are also read first, so "ti" is ティ rather than ち.
+    Guideline,
+}
+
+// Which kana orthography the input is in. Historical spellings (けふ, てふてふ, かは)
+// are modernized before conversion, so they are read as pronounced: "kyou",
+// "chouchou", "kawa". See orthography::modernize.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Orthography {
+    Modern,
+    Historical,
+}
+
+// How the particles は, へ and を are written in romaji
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Particles {
+    // As spelled: ha, he, wo
+    Spelling,
+    // As pronounced: wa, e, o. は and へ only read as particles when they stand
+    // apart from the hiragana around them, by spaces or other scripts (わたし は,
+    // 私は); "わたしは" can't be told apart from はな. を is always pronounced o.
+    Pronunciation,
+}
+
+// Long vowels as written in romaji: (vowel, macron, circumflex)
+const LONG_VOWELS: [(char, char, char); 5] = [
+    ('a', 'ā', 'â'),
+    ('i', 'ī', 'î'),
+    ('u', 'ū', 'û'),
+    ('e', 'ē', 'ê'),
+    ('o', 'ō', 'ô'),
+];
+
+const KUNREI: [(&str, &str); 20] = [
+    ("し", "si"),
+    ("しゃ", "sya"),
+    ("しゅ", "syu"),
+    ("しょ", "syo"),
+    ("じ", "zi"),
+    ("じゃ", "zya"),
+    ("じゅ", "zyu"),
+    ("じょ", "zyo"),
+    ("ち", "ti"),
+    ("ちゃ", "tya"),
+    ("ちゅ", "tyu"),
+    ("ちょ", "tyo"),
+    ("つ", "tu"),
+    ("ふ", "hu"),
+    ("ぢ", "zi"),
+    ("づ", "zu"),
+    ("ぢゃ", "zya"),
+    ("ぢゅ", "zyu"),
+    ("ぢょ", "zyo"),
+    ("を", "o"),
+];
+
+// Spellings the loanword guideline only writes. Small ヵ and ヶ are written as
+// their full-size kana and read back as か and け, and を is written "o" since
+// "wo" is ウォ
+const WRITTEN_ONLY: [(&str, &str); 3] = [("ゕ", "ka"), ("ゖ", "ke"), ("を", "o")];
+
+// Nihon-shiki only differs from Kunrei-shiki in keeping the ぢ/づ and を spellings
+const NIHON: [(&str, &str); 6] = [
+    ("ぢ", "di"),
+    ("づ", "du"),
+    ("ぢゃ", "dya"),
+    ("ぢゅ", "dyu"),
+    ("ぢょ", "dyo"),
+    ("を", "wo"),
+];
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConverterBuilder {
+    options: Options,
+    // Built-in tables when not given
+    tables: Option<Tables>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Options {
+    target: Target,
+    romanization: Romanization,
+    punctuation: Punctuation,
+    unknown: Unknown,
+    case: Case,
+    long_vowel: LongVowel,
+    dangling_sokuon: DanglingSokuon,
+    loanwords: Loanwords,
+    orthography: Orthography,
+    particles: Particles,
+}
+
+// A configured conversion to one target script.
+//
+// Converters own their tables (the built-in ones, or custom tables::Tables),
+// so they are built once and can be shared across threads (they are Send + Sync):
+//
+// let converter = Converter::builder().target(Target::Romaji).long_vowel(LongVowel::Macron).build();
+// converter.convert("とうきょう") // "tōkyō"
+//
+// Unlike `to_romaji` and friends, mixed-script input is converted run by run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Converter {
+    options: Options,
+    tables: Tables,
+    max_romaji_len: usize,
+    max_kana_len: usize,
+    max_katakana_len: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Class {
+    Romaji,
+    Kana,
+    // Spaces and numbers, which are the same in every script
+    Neutral,
+    Unknown,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            target: Target::Hiragana,
+            romanization: Romanization::Hepburn,
+            punctuation: Punctuation::Convert,
+            unknown: Unknown::Keep,
+            case: Case::Lower,
+            long_vowel: LongVowel::Spelled,
+            dangling_sokuon: DanglingSokuon::Apostrophe,
+            loanwords: Loanwords::Ime,
+            orthography: Orthography::Modern,
+            particles: Particles::Spelling,
+        }
+    }
+}
+
+impl ConverterBuilder {
+    pub fn target(mut self, target: Target) -> Self {
+        self.options.target = target;
+        self
+    }
+
+    pub fn romanization(mut self, romanization: Romanization) -> Self {
+        self.options.romanization = romanization;
+        self
+    }
+
+    pub fn punctuation(mut self, punctuation: Punctuation) -> Self {
+        self.options.punctuation = punctuation;
+        self
+    }
+
+    pub fn unknown(mut self, unknown: Unknown) -> Self {
+        self.options.unknown = unknown;
+        self
+    }
+
+    pub fn case(mut self, case: Case) -> Self {
+        self.options.case = case;
+        self
+    }
+
+    pub fn long_vowel(mut self, long_vowel: LongVowel) -> Self {
+        self.options.long_vowel = long_vowel;
+        self
+    }
+
+    pub fn dangling_sokuon(mut self, dangling_sokuon: DanglingSokuon) -> Self {
+        self.options.dangling_sokuon = dangling_sokuon;
+        self
+    }
+
+    pub fn loanwords(mut self, loanwords: Loanwords) -> Self {
+        self.options.loanwords = loanwords;
+        self
+    }
+
+    pub fn orthography(mut self, orthography: Orthography) -> Self {
+        self.options.orthography = orthography;
+        self
+    }
+
+    pub fn particles(mut self, particles: Particles) -> Self {
+        self.options.particles = particles;
+        self
+    }
+
+    pub fn tables(mut self, tables: Tables) -> Self {
+        self.tables = Some(tables);
+        self
+    }
+
+    pub fn build(self) -> Converter {
+        let options = self.options;
+        let mut tables = self.tables.unwrap_or_else(Tables::builtin);
+
+        if options.loanwords == Loanwords::Guideline {
+            for (romaji, kana) in LOANWORDS_TO_HIRAGANA.entries() {
+                tables.insert(Table::Romaji, romaji, kana);
+            }
+            for (kana, romaji) in WRITTEN_ONLY.iter() {
+                tables
+                    .hiragana_to_romaji
+                    .insert(kana.to_string(), romaji.to_string());
+            }
+        }
+
+        let overrides: &[(&str, &str)] = match options.romanization {
+            Romanization::Hepburn => &[],
+            Romanization::Kunrei => &KUNREI,
+            Romanization::Nihon => &KUNREI,
+        };
+        let nihon: &[(&str, &str)] = match options.romanization {
+            Romanization::Nihon => &NIHON,
+            _ => &[],
+        };
+
+        for (kana, romaji) in overrides.iter().chain(nihon.iter()) {
+            tables
+                .hiragana_to_romaji
+                .insert(kana.to_string(), romaji.to_string());
+        }
+
+        if options.punctuation == Punctuation::Keep {
+            let is_punct = |romaji: &String| romaji.chars().all(|ch| ch.is_ascii_punctuation());
+
+            tables
+                .romaji_to_hiragana
+                .retain(|romaji, _| !is_punct(romaji));
+            tables
+                .hiragana_to_romaji
+                .retain(|_, romaji| !is_punct(romaji));
+        }
+
+        Converter {
+            options,
+            max_romaji_len: Tables::max_key_len(&tables.romaji_to_hiragana),
+            max_kana_len: Tables::max_key_len(&tables.hiragana_to_romaji),
+            max_katakana_len: Tables::max_key_len(&tables.hiragana_to_katakana),
+            tables,
+        }
+    }
+}
+
+impl Default for Converter {
+    fn default() -> Self {
+        ConverterBuilder::default().build()
+    }
+}
+
+impl Converter {
+    pub fn builder() -> ConverterBuilder {
+        ConverterBuilder::default()
+    }
+
+    pub fn target(&self) -> Target {
+        self.options.target
+    }
+
+    pub fn convert(&self, input: &str) -> String {
+        self.convert_aligned(input).text
+    }
+
+    // Strict conversion: fails with every fragment of the input that couldn't be
+    // converted, instead of handling it by the unknown character policy
+    pub fn try_convert(&self, input: &str) -> Result<String, Error> {
+        let lenient = self.convert_lenient(input);
+
+        if lenient.warnings.is_empty() {
+            Ok(lenient.text)
+        } else {
+            Err(Error::Unconvertible(lenient.warnings))
+        }
+    }
+
+    // Best-effort conversion, along with every fragment that couldn't be converted
+    pub fn convert_lenient(&self, input: &str) -> Lenient {
+        let pieces = self.pieces(input);
+        let chars: Vec<char> = input.chars().collect();
+        let mut warnings: Vec<Fragment> = Vec::new();
+
+        for piece in pieces.0.iter().filter(|piece| !piece.converted) {
+            match warnings.last_mut() {
+                Some(last) if last.input.end == piece.input.start => {
+                    last.input.end = piece.input.end;
+                }
+                _ => warnings.push(Fragment {
+                    text: String::new(),
+                    input: piece.input.clone(),
+                }),
+            }
+        }
+
+        for fragment in warnings.iter_mut() {
+            fragment.text = chars[fragment.input.clone()].iter().collect();
+        }
+
+        Lenient {
+            text: pieces.aligned().text,
+            warnings,
+        }
+    }
+
+    // Convert, keeping track of which input chars every part of the output came from
+    pub fn convert_aligned(&self, input: &str) -> Aligned {
+        self.pieces(input).aligned()
+    }
+
+    fn pieces(&self, input: &str) -> Pieces {
+        let mut pieces = Pieces::default();
+        let chars: Vec<(usize, char)> = input.chars().enumerate().collect();
+
+        for (class, run) in &chars.iter().group_by(|(_, ch)| class(*ch)) {
+            let run: Vec<(usize, char)> = run.copied().collect();
+
+            match class {
+                Class::Romaji => self.convert_romaji(&run, &mut pieces),
+                Class::Kana => {
+                    let before = run[0].0.checked_sub(1).map(|index| chars[index].1);
+                    let after = chars.get(run[run.len() - 1].0 + 1).map(|(_, ch)| *ch);
+                    self.convert_kana(&run, (before, after), &mut pieces)
+                }
+                Class::Neutral => {
+                    for (index, ch) in run {
+                        pieces.push(index..index + 1, ch.to_string());
+                    }
+                }
+                Class::Unknown => {
+                    for (index, ch) in run {
+                        pieces.push_unknown(index..index + 1, self.unknown(ch));
+                    }
+                }
+            }
+        }
+
+        if self.options.target == Target::Romaji {
+            self.apply_case(&mut pieces.0);
+        }
+
+        pieces
+    }
+
+    fn convert_romaji(&self, run: &[(usize, char)], pieces: &mut Pieces) {
+        if self.options.target == Target::Romaji {
+            for &(index, ch) in run {
+                pieces.push(index..index + 1, ch.to_string());
+            }
+            return;
+        }
+
+        // Long vowel letters are read as the vowel followed by ー
+        let chars: Vec<(usize, char)> = run
+            .iter()
+            .flat_map(|&(index, ch)| match long_vowel_letter(ch) {
+                Some(vowel) => vec![(index, vowel), (index, CHOONPU)],
+                None => vec![(index, ch.to_ascii_lowercase())],
+            })
+            .collect();
+        let letters: Vec<char> = chars.iter().map(|(_, ch)| *ch).collect();
+        let span = |pos: usize, len: usize| chars[pos].0..chars[pos + len - 1].0 + 1;
+
+        let mut vowel = None;
+        let mut pos = 0;
+
+        while pos < letters.len() {
+            let ch = letters[pos];
+
+            if ch == CHOONPU {
+                let kana = match (self.options.target, vowel) {
+                    (Target::Hiragana, Some(vowel)) => long_vowel_kana(vowel),
+                    _ => CHOONPU,
+                };
+                pieces.push(span(pos, 1), kana.to_string());
+                pos += 1;
+                continue;
+            }
+
+            if ch == 'n' && letters.get(pos + 1) == Some(&'\'') {
+                pieces.push(span(pos, 2), self.kana(&HATSUON.to_string()));
+                vowel = None;
+                pos += 2;
+                continue;
+            }
+
+            // "nn" before a vowel is ん followed by a na-row mora (こんにちは, "konnichiwa")
+            let after = letters.get(pos + 2).copied().unwrap_or_default();
+            if ch == 'n' && letters.get(pos + 1) == Some(&'n') && "aiueoy".contains(after) {
+                pieces.push(span(pos, 1), self.kana(&HATSUON.to_string()));
+                vowel = None;
+                pos += 1;
+                continue;
+            }
+
+            if let Some(partial) = self.geminate_at(&letters, pos) {
+                pieces.push(span(pos, 1), self.kana(&partial.to_string()));
+                vowel = None;
+                pos += 1;
+                continue;
+            }
+
+            match self.romaji_at(&letters, pos) {
+                Some((len, kana)) => {
+                    pieces.push(span(pos, len), self.kana(kana));
+                    vowel = letters[pos + len - 1]
+                        .is_ascii_alphabetic()
+                        .then(|| letters[pos + len - 1]);
+                    pos += len;
+                }
+
+                None => {
+                    if let Some(kana) = self.final_at(vowel, ch) {
+                        pieces.push(span(pos, 1), self.kana(kana));
+                    } else if ch.is_ascii_punctuation() {
+                        pieces.push(span(pos, 1), ch.to_string());
+                    } else {
+                        pieces.push_unknown(span(pos, 1), self.unknown(ch));
+                    }
+                    vowel = None;
+                    pos += 1;
+                }
+            }
+        }
+    }
+
+    // `around` is the chars before and after the run, if any
+    fn convert_kana(
+        &self,
+        run: &[(usize, char)],
+        around: (Option<char>, Option<char>),
+        pieces: &mut Pieces,
+    ) {
+        // Hiragana for every char, paired with the index of the char it came from (ヷ → わ゛)
+        let mut hiragana: Vec<(usize, char)> = run
+            .iter()
+            .flat_map(|&(index, ch)| {
+                self.tables
+                    .hiragana(&ch.to_string())
+                    .chars()
+                    .map(|hiragana| (index, hiragana))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+
+        if self.options.orthography == Orthography::Historical {
+            // Whether the run continues a word, eg. after kanji
+            let medial = around.0.is_some_and(|before| !is_boundary(before));
+            hiragana = orthography::modernize_chars(&hiragana, medial);
+        }
+
+        match self.options.target {
+            Target::Romaji => {
+                let particles = self.particles(run, around);
+                self.romanize(&hiragana, &particles, pieces)
+            }
+            Target::Hiragana => {
+                for (index, ch) in hiragana {
+                    if self.is_kana(ch) {
+                        pieces.push(index..index + 1, ch.to_string());
+                    } else {
+                        pieces.push_unknown(index..index + 1, self.unknown(ch));
+                    }
+                }
+            }
+            Target::Katakana => {
+                let chars: Vec<char> = hiragana.iter().map(|(_, ch)| *ch).collect();
+                let mut pos = 0;
+
+                while pos < chars.len() {
+                    match self.katakana_at(&chars, pos) {
+                        Some((len, katakana)) => {
+                            pieces.push(hiragana[pos].0..hiragana[pos + len - 1].0 + 1, katakana);
+                            pos += len;
+                        }
+                        None => {
+                            let (index, ch) = hiragana[pos];
+                            if self.is_kana(ch) {
+                                pieces.push(index..index + 1, ch.to_string());
+                            } else {
+                                pieces.push_unknown(index..index + 1, self.unknown(ch));
+                            }
+                            pos += 1;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    // Particles pronounced apart from their spelling, by the index of their char:
+    // は and へ standing apart from the hiragana around them (わたし は, 私は), and every を
+    fn particles(
+        &self,
+        run: &[(usize, char)],
+        (before, after): (Option<char>, Option<char>),
+    ) -> Vec<(usize, &'static str)> {
+        if self.options.particles == Particles::Spelling {
+            return Vec::new();
+        }
+
+        let is_hiragana = |ch: Option<char>| ch.is_some_and(|ch| parse(ch) == LexItem::Hiragana);
+
+        run.iter()
+            .enumerate()
+            .filter_map(|(pos, &(index, ch))| {
+                let last = pos.checked_sub(1).map(|pos| run[pos].1).or(before);
+                let next = run.get(pos + 1).map(|(_, ch)| *ch).or(after);
+                let apart = !is_hiragana(last) && !is_hiragana(next);
+
+                match ch {
+                    'は' if apart => Some((index, "wa")),
+                    'へ' if apart => Some((index, "e")),
+                    'を' => Some((index, "o")),
+                    _ => None,
+                }
+            })
+            .collect()
+    }
+
+    fn romanize(
+        &self,
+        hiragana: &[(usize, char)],
+        particles: &[(usize, &'static str)],
+        pieces: &mut Pieces,
+    ) {
+        let chars: Vec<char> = hiragana.iter().map(|(_, ch)| *ch).collect();
+        let mut tokens: Vec<(String, Option<&str>, Range<usize>)> = Vec::new();
+        let mut pos = 0;
+
+        while pos < chars.len() {
+            let longest = self.max_kana_len.min(chars.len() - pos);
+            let found = (1..=longest).rev().find_map(|len| {
+                let kana: String = chars[pos..pos + len].iter().collect();
+                self.tables
+                    .hiragana_to_romaji
+                    .get(&kana)
+                    .map(|romaji| (kana, romaji.as_str(), len))
+            });
+            let particle = particles
+                .iter()
+                .find(|(index, _)| *index == hiragana[pos].0 && "はへを".contains(chars[pos]));
+            let (kana, romaji, len) = match (particle, found) {
+                (Some((_, romaji)), _) => (chars[pos].to_string(), Some(*romaji), 1),
+                (None, Some((kana, romaji, len))) => (kana, Some(romaji), len),
+                (None, None) => (chars[pos].to_string(), None, 1),
+            };
+
+            tokens.push((kana, romaji, hiragana[pos].0..hiragana[pos + len - 1].0 + 1));
+            pos += len;
+        }
+
+        // Whether the last romaji vowel written can still be lengthened
+        let mut lengthens = false;
+
+        for (i, (kana, romaji, span)) in tokens.iter().enumerate() {
+            let next = tokens.get(i + 1).and_then(|(_, romaji, _)| *romaji);
+            let first = kana.chars().next().unwrap();
+            let span = span.clone();
+
+            match (first, romaji) {
+                (SOKUON, _) if next.is_some_and(starts_with_geminable) => {
+                    let next = next.unwrap();
+                    match (self.options.romanization, next.starts_with("ch")) {
+                        (Romanization::Hepburn, true) => pieces.push(span, "t".to_string()),
+                        _ => pieces.push(span, next[..1].to_string()),
+                    }
+                    lengthens = false;
+                }
+
+                (SOKUON, _) => {
+                    match self.options.dangling_sokuon {
+                        DanglingSokuon::Apostrophe => pieces.push(span, "'".to_string()),
+                        DanglingSokuon::H => pieces.push(span, "h".to_string()),
+                        DanglingSokuon::Omit => pieces.push(span, String::new()),
+                        DanglingSokuon::Unknown => pieces.push_unknown(span, self.unknown(SOKUON)),
+                    }
+                    lengthens = false;
+                }
+
+                (HATSUON, Some(romaji)) => {
+                    let mut text = romaji.to_string();
+                    // An apostrophe keeps ん apart from a following vowel or ん (こんや, "kon'ya")
+                    if next.is_some_and(|next| {
+                        next == "n" || next.starts_with(|ch| "aiueoy".contains(ch))
+                    }) {
+                        text.push('\'');
+                    }
+                    pieces.push(span, text);
+                    lengthens = false;
+                }
+
+                (CHOONPU, _) if lengthens => {
+                    pieces.lengthen(span, |vowel| self.long_vowel(vowel));
+                    lengthens = false;
+                }
+
+                (_, Some(romaji)) if lengthens && self.is_lengthening(pieces.last(), romaji) => {
+                    pieces.lengthen(span, |vowel| self.long_vowel(vowel));
+                    lengthens = false;
+                }
+
+                (_, Some(romaji)) => {
+                    pieces.push(span, romaji.to_string());
+                    lengthens = romaji.ends_with(|ch| "aiueo".contains(ch));
+                }
+
+                (first, None) if is_punctuation(first) => {
+                    pieces.push(span, kana.clone());
+                    lengthens = false;
+                }
+
+                (_, None) => {
+                    let text = kana.chars().map(|ch| self.unknown(ch)).collect();
+                    pieces.push_unknown(span, text);
+                    lengthens = false;
+                }
+            }
+        }
+    }
+
+    // True when a bare vowel `romaji` lengthens the `last` vowel written (とう, ああ)
+    fn is_lengthening(&self, last: Option<char>, romaji: &str) -> bool {
+        if self.options.long_vowel == LongVowel::Spelled {
+            return false;
+        }
+
+        match (last, romaji) {
+            (Some(last), romaji) if romaji.len() == 1 => {
+                let next = romaji.chars().next().unwrap();
+                last == next || (last, next) == ('o', 'u')
+            }
+            _ => false,
+        }
+    }
+
+    // A romaji vowel written long, going by the long vowel policy
+    fn long_vowel(&self, vowel: char) -> String {
+        let long = LONG_VOWELS.iter().find(|(short, _, _)| *short == vowel);
+
+        match (self.options.long_vowel, long) {
+            (LongVowel::Macron, Some((_, macron, _))) => macron.to_string(),
+            (LongVowel::Circumflex, Some((_, _, circumflex))) => circumflex.to_string(),
+            _ => format!("{}{}", vowel, vowel),
+        }
+    }
+
+    // っ or ん for the first letter of a geminate ("kk", "mb", "dd")
+    fn geminate_at(&self, chars: &[char], pos: usize) -> Option<char> {
+        let first = *chars.get(pos)?;
+        let second = *chars.get(pos + 1)?;
+        self.romaji_at(chars, pos + 1)?;
+
+        let key: String = [first, second].iter().collect();
+        match self.tables.geminates_to_hiragana.get(&key) {
+            Some(partial) => partial.chars().next(),
+            None if first == second && starts_with_geminable(&key) => Some(SOKUON),
+            None => None,
+        }
+    }
+
+    // Kana for a syllable-final consonant, after `vowel` (Ainu ㇻ for r after a)
+    fn final_at(&self, vowel: Option<char>, consonant: char) -> Option<&str> {
+        let vowel = vowel?;
+        let after_vowel: String = [vowel, consonant].iter().collect();
+
+        self.tables
+            .finals
+            .get(&after_vowel)
+            .or_else(|| self.tables.finals.get(consonant.to_string().as_str()))
+            .map(|kana| kana.as_str())
+    }
+
+    // Longest romaji key at `pos`, with its length in chars
+    fn romaji_at(&self, chars: &[char], pos: usize) -> Option<(usize, &str)> {
+        let longest = self.max_romaji_len.min(chars.len().saturating_sub(pos));
+
+        (1..=longest).rev().find_map(|len| {
+            let romaji: String = chars[pos..pos + len].iter().collect();
+            self.tables
+                .romaji_to_hiragana
+                .get(&romaji)
+                .map(|kana| (len, kana.as_str()))
+        })
+    }
+
+    // Hiragana in the target kana script
+    fn kana(&self, hiragana: &str) -> String {
+        match self.options.target {
+            Target::Katakana => self.katakana(hiragana),
+            _ => hiragana.to_string(),
+        }
+    }
+
+    fn katakana(&self, hiragana: &str) -> String {
+        let chars: Vec<char> = hiragana.chars().collect();
+        let mut result = String::with_capacity(hiragana.len());
+        let mut pos = 0;
+
+        while pos < chars.len() {
+            let (len, katakana) = self
+                .katakana_at(&chars, pos)
+                .unwrap_or_else(|| (1, chars[pos].to_string()));
+            result.push_str(&katakana);
+            pos += len;
+        }
+
+        result
+    }
+
+    // Longest hiragana key at `pos` and its katakana
+    fn katakana_at(&self, chars: &[char], pos: usize) -> Option<(usize, String)> {
+        let longest = self.max_katakana_len.min(chars.len() - pos);
+
+        (1..=longest).rev().find_map(|len| {
+            let kana: String = chars[pos..pos + len].iter().collect();
+            self.tables
+                .hiragana_to_katakana
+                .get(&kana)
+                .map(|katakana| (len, katakana.clone()))
+        })
+    }
+
+    // Whether a char of a kana run (as hiragana) is in the tables, so it can be
+    // kept as is in kana output. ゛ is, for ヷ → わ゛, but ヽ, ゟ or ㇼ without the
+    // Ainu tables aren't.
+    fn is_kana(&self, ch: char) -> bool {
+        let kana = ch.to_string();
+
+        [CHOONPU, DAKUTEN, HANDAKUTEN].contains(&ch)
+            || is_punctuation(ch)
+            || self.tables.hiragana_to_katakana.contains_key(&kana)
+            || self.tables.hiragana_to_romaji.contains_key(&kana)
+    }
+
+    fn unknown(&self, ch: char) -> String {
+        match self.options.unknown {
+            Unknown::Keep => ch.to_string(),
+            Unknown::Drop => String::new(),
+            Unknown::Replace(replacement) => replacement.to_string(),
+        }
+    }
+
+    // Case is applied piece by piece so the alignment holds, carrying over
+    // whether the next letter starts a word
+    fn apply_case(&self, pieces: &mut [Piece]) {
+        let mut word_start = true;
+
+        for piece in pieces.iter_mut() {
+            piece.text = match self.options.case {
+                Case::Lower => piece.text.to_lowercase(),
+                Case::Upper => piece.text.to_uppercase(),
+                Case::Capitalized => piece
+                    .text
+                    .to_lowercase()
+                    .chars()
+                    .flat_map(|ch| {
+                        let upper = word_start && ch.is_alphabetic();
+                        word_start = ch.is_whitespace() || (word_start && !ch.is_alphabetic());
+
+                        if upper {
+                            ch.to_uppercase().collect::<Vec<char>>()
+                        } else {
+                            vec![ch]
+                        }
+                    })
+                    .collect(),
+            };
+        }
+    }
+}
+
+// Converted text with the input chars every part of it came from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Aligned {
+    pub text: String,
+    // In input order, covering the input and the text without gaps
+    pub spans: Vec<Span>,
+}
+
+// An input span and the output it converted to, as char offsets (end is exclusive).
+// The output is empty when the input was dropped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Span {
+    pub input: Range<usize>,
+    pub output: Range<usize>,
+}
+
+impl Aligned {
+    // The span converted from the input char at `index`
+    pub fn from_input(&self, index: usize) -> Option<&Span> {
+        self.spans.iter().find(|span| span.input.contains(&index))
+    }
+
+    // The span the output char at `index` belongs to
+    pub fn from_output(&self, index: usize) -> Option<&Span> {
+        self.spans.iter().find(|span| span.output.contains(&index))
+    }
+}
+
+// An input fragment that couldn't be converted, with its char offsets
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fragment {
+    pub text: String,
+    pub input: Range<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lenient {
+    pub text: String,
+    pub warnings: Vec<Fragment>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Piece {
+    input: Range<usize>,
+    text: String,
+    // False when the input couldn't be converted and the text is from the unknown policy
+    converted: bool,
+}
+
+// Output built up piece by piece, each piece knowing its input span
+#[derive(Debug, Default)]
+struct Pieces(Vec<Piece>);
+
+impl Pieces {
+    fn push(&mut self, input: Range<usize>, text: String) {
+        self.add(input, text, true);
+    }
+
+    fn push_unknown(&mut self, input: Range<usize>, text: String) {
+        self.add(input, text, false);
+    }
+
+    // Pieces from the same input char (ō → おう) are merged into one
+    fn add(&mut self, input: Range<usize>, text: String, converted: bool) {
+        match self.0.last_mut() {
+            Some(last) if input.start < last.input.end => {
+                last.input.end = last.input.end.max(input.end);
+                last.text.push_str(&text);
+                last.converted &= converted;
+            }
+            _ => self.0.push(Piece {
+                input,
+                text,
+                converted,
+            }),
+        }
+    }
+
+    fn last(&self) -> Option<char> {
+        self.0.last().and_then(|piece| piece.text.chars().last())
+    }
+
+    // Rewrite the last vowel written as a long vowel, taking in the input that lengthened it
+    fn lengthen(&mut self, input: Range<usize>, long_vowel: impl Fn(char) -> String) {
+        if let Some(last) = self.0.last_mut() {
+            if let Some(vowel) = last.text.pop() {
+                last.text.push_str(&long_vowel(vowel));
+            }
+            last.input.end = input.end;
+        }
+    }
+
+    fn aligned(self) -> Aligned {
+        let mut text = String::new();
+        let mut offset = 0;
+        let mut spans = Vec::with_capacity(self.0.len());
+
+        for piece in self.0 {
+            let len = piece.text.chars().count();
+            text.push_str(&piece.text);
+            spans.push(Span {
+                input: piece.input,
+                output: offset..offset + len,
+            });
+            offset += len;
+        }
+
+        Aligned { text, spans }
+    }
+}
+
+fn class(ch: char) -> Class {
+    match parse(ch) {
+        LexItem::Rom => Class::Romaji,
+        LexItem::Hiragana | LexItem::Katakana => Class::Kana,
+        LexItem::Punct if ch.is_ascii() => Class::Romaji,
+        LexItem::Punct => Class::Kana,
+        LexItem::Num | LexItem::Space => Class::Neutral,
+        _ => Class::Unknown,
+    }
+}
+
+fn is_boundary(ch: char) -> bool {
+    ch.is_whitespace() || is_punctuation(ch)
+}
+
+// The plain vowel for a macron or circumflex vowel, eg. ō → o
+fn long_vowel_letter(ch: char) -> Option<char> {
+    let lower = ch.to_lowercase().next()?;
+
+    LONG_VOWELS
+        .iter()
+        .find(|(_, macron, circumflex)| lower == *macron || lower == *circumflex)
+        .map(|(vowel, _, _)| *vowel)
+}
+
+// The kana that lengthens a vowel in hiragana: お is lengthened with う
+fn long_vowel_kana(vowel: char) -> char {
+    match vowel {
+        'o' => 'う',
+        vowel => kana::vowel_kana(vowel).unwrap_or(CHOONPU),
+    }
+}
+
+// Romaji starting with a consonant that can be doubled for っ
+fn starts_with_geminable(romaji: &str) -> bool {
+    romaji.starts_with(|ch: char| ch.is_ascii_alphabetic() && !"aiueon".contains(ch))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::thread;
+
+    fn romaji() -> ConverterBuilder {
+        Converter::builder().target(Target::Romaji)
+    }
+
+    #[test]
+    fn test_convert_to_kana() {
+        let hiragana = Converter::default();
+        assert_eq!(hiragana.convert("shinkansen"), "しんかんせん");
+        assert_eq!(hiragana.convert("Kippu"), "きっぷ");
+        assert_eq!(hiragana.convert("kon'ya"), "こんや");
+        assert_eq!(hiragana.convert("konnichiwa"), "こんにちわ");
+        assert_eq!(hiragana.convert("n'n"), "んん");
+        assert_eq!(hiragana.convert("beddo"), "べっど");
+        assert_eq!(hiragana.convert("matcha"), "まっちゃ");
+        assert_eq!(hiragana.convert("kippu to キップ"), "きっぷ と きっぷ");
+        assert_eq!(hiragana.convert("tōkyō"), "とうきょう");
+
+        let katakana = Converter::builder().target(Target::Katakana).build();
+        assert_eq!(katakana.convert("tōkyō"), "トーキョー");
+        assert_eq!(katakana.convert("けしゴム"), "ケシゴム");
+        assert_eq!(katakana.convert("va"), "ヴァ");
+    }
+
+    #[test]
+    fn test_convert_to_romaji() {
+        let converter = romaji().build();
+        assert_eq!(converter.convert("きっぷ"), "kippu");
+        assert_eq!(converter.convert("ベッド"), "beddo");
+        assert_eq!(converter.convert("こんや"), "kon'ya");
+        assert_eq!(converter.convert("こんにちは"), "konnichiha");
+        assert_eq!(converter.convert("んん"), "n'n");
+        assert_eq!(converter.convert("まっちゃ"), "matcha");
+        assert_eq!(
+            converter.convert("こんじゅ が すごい だ"),
+            "konju ga sugoi da"
+        );
+        assert_eq!(converter.convert("けしゴム"), "keshigomu");
+    }
+
+    #[test]
+    fn test_romanization() {
+        let kunrei = romaji().romanization(Romanization::Kunrei).build();
+        assert_eq!(kunrei.convert("しんぶん"), "sinbun");
+        assert_eq!(kunrei.convert("ちぢむ"), "tizimu");
+        assert_eq!(kunrei.convert("まっちゃ"), "mattya");
+
+        let nihon = romaji().romanization(Romanization::Nihon).build();
+        assert_eq!(nihon.convert("ちぢむ"), "tidimu");
+        assert_eq!(nihon.convert("ふじ"), "huzi");
+    }
+
+    #[test]
+    fn test_loanwords() {
+        let ime = romaji().build();
+        assert_eq!(ime.convert("パーティー"), "paathii");
+
+        let guideline = romaji()
+            .loanwords(Loanwords::Guideline)
+            .long_vowel(LongVowel::Macron)
+            .build();
+        assert_eq!(guideline.convert("パーティー"), "pātī");
+        assert_eq!(guideline.convert("ディズニー"), "dizunī");
+        assert_eq!(guideline.convert("トゥール"), "tūru");
+        assert_eq!(guideline.convert("ウォッカ"), "wokka");
+        assert_eq!(guideline.convert("ヵ ヶ"), "ka ke");
+        assert_eq!(guideline.convert("チケット"), "chiketto");
+
+        let katakana = Converter::builder()
+            .target(Target::Katakana)
+            .loanwords(Loanwords::Guideline)
+            .build();
+        assert_eq!(katakana.convert("pātī"), "パーティー");
+        assert_eq!(katakana.convert("dyūku"), "デューク");
+        assert_eq!(katakana.convert("chiketto"), "チケット");
+        assert_eq!(katakana.convert("wokka"), "ウォッカ");
+        assert_eq!(katakana.convert(&guideline.convert("ウォッカ")), "ウォッカ");
+        assert_eq!(guideline.convert("これを よむ"), "koreo yomu");
+
+        for (spelling, kana) in LOANWORDS_TO_HIRAGANA.entries() {
+            let written = katakana.convert(spelling);
+            assert_eq!(katakana.convert(kana), written);
+            assert_eq!(guideline.convert(&written), *spelling);
+        }
+    }
+
+    #[test]
+    fn test_orthography() {
+        let historical = romaji().orthography(Orthography::Historical).build();
+        assert_eq!(historical.convert("けふ"), "kyou");
+        assert_eq!(historical.convert("てふてふ"), "chouchou");
+        assert_eq!(historical.convert("かはの ほとり"), "kawano hotori");
+        assert_eq!(historical.convert("思ふ"), "思u");
+        assert_eq!(
+            historical.convert("ちぢむ つづく"),
+            romaji().build().convert("ちぢむ つづく")
+        );
+        assert_eq!(romaji().build().convert("けふ"), "kefu");
+
+        let hiragana = Converter::builder()
+            .orthography(Orthography::Historical)
+            .build();
+        assert_eq!(hiragana.convert("ゐなか ヱ"), "いなか え");
+
+        let aligned = hiragana.convert_aligned("てふ");
+        assert_eq!(spans(&aligned), vec![(0..1, 0..2), (1..2, 2..3)]);
+    }
+
+    #[test]
+    fn test_particles() {
+        let spelling = romaji().build();
+        assert_eq!(spelling.convert("わたし は"), "watashi ha");
+
+        let pronunciation = romaji().particles(Particles::Pronunciation).build();
+        assert_eq!(
+            pronunciation.convert("わたし は がくせい"),
+            "watashi wa gakusei"
+        );
+        assert_eq!(pronunciation.convert("私は学生"), "私wa学生");
+        assert_eq!(pronunciation.convert("東京へ いく"), "東京e iku");
+        assert_eq!(pronunciation.convert("ほんを よむ"), "hon'o yomu");
+        assert_eq!(pronunciation.convert("はな へや"), "hana heya");
+        assert_eq!(pronunciation.convert("わたしは"), "watashiha");
+        assert_eq!(pronunciation.convert("コーヒーは"), "koohiiwa");
+    }
+
+    #[test]
+    fn test_long_vowel() {
+        assert_eq!(romaji().build().convert("ラーメン"), "raamen");
+        assert_eq!(romaji().build().convert("とうきょう"), "toukyou");
+
+        let macron = romaji().long_vowel(LongVowel::Macron).build();
+        assert_eq!(macron.convert("とうきょう"), "tōkyō");
+        assert_eq!(macron.convert("ラーメン"), "rāmen");
+        assert_eq!(macron.convert("けいさつ"), "keisatsu");
+
+        let circumflex = romaji().long_vowel(LongVowel::Circumflex).build();
+        assert_eq!(circumflex.convert("とうきょう"), "tôkyô");
+    }
+
+    #[test]
+    fn test_dangling_sokuon() {
+        let apostrophe = romaji().build();
+        assert_eq!(apostrophe.convert("えっ！"), "e'！");
+        assert_eq!(apostrophe.convert("あっ"), "a'");
+        assert_eq!(apostrophe.convert("ッ"), "'");
+        assert_eq!(apostrophe.convert("きっう"), "ki'u");
+        assert_eq!(apostrophe.convert("ウッン"), "u'n");
+        assert_eq!(apostrophe.convert("あっ、そう"), "a',sou");
+
+        let h = romaji().dangling_sokuon(DanglingSokuon::H).build();
+        assert_eq!(h.convert("えっ！"), "eh！");
+        assert_eq!(h.convert("ッ"), "h");
+
+        let omit = romaji().dangling_sokuon(DanglingSokuon::Omit).build();
+        assert_eq!(omit.convert("えっ！"), "e！");
+
+        let unknown = romaji()
+            .dangling_sokuon(DanglingSokuon::Unknown)
+            .unknown(Unknown::Replace('?'))
+            .build();
+        assert_eq!(unknown.convert("えっ"), "e?");
+    }
+
+    #[test]
+    fn test_punctuation() {
+        assert_eq!(romaji().build().convert("きっぷ。"), "kippu.");
+        assert_eq!(Converter::default().convert("kippu."), "きっぷ。");
+
+        let keep = romaji().punctuation(Punctuation::Keep).build();
+        assert_eq!(keep.convert("きっぷ。"), "kippu。");
+
+        let keep = Converter::builder().punctuation(Punctuation::Keep).build();
+        assert_eq!(keep.convert("kippu."), "きっぷ.");
+    }
+
+    #[test]
+    fn test_unknown() {
+        assert_eq!(Converter::default().convert("漢字 kana"), "漢字 かな");
+
+        let drop = Converter::builder().unknown(Unknown::Drop).build();
+        assert_eq!(drop.convert("漢字 kana"), " かな");
+
+        let replace = Converter::builder().unknown(Unknown::Replace('?')).build();
+        assert_eq!(replace.convert("漢字 kaq"), "?? か?");
+    }
+
+    #[test]
+    fn test_case() {
+        let upper = romaji().case(Case::Upper).build();
+        assert_eq!(upper.convert("きっぷ"), "KIPPU");
+
+        let capitalized = romaji().case(Case::Capitalized).build();
+        assert_eq!(capitalized.convert("とうきょう えき"), "Toukyou Eki");
+        assert_eq!(capitalized.convert("SHINKANSEN"), "Shinkansen");
+    }
+
+    fn spans(aligned: &Aligned) -> Vec<(Range<usize>, Range<usize>)> {
+        aligned
+            .spans
+            .iter()
+            .map(|span| (span.input.clone(), span.output.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn test_convert_aligned() {
+        let kippu = Converter::default().convert_aligned("kippu");
+        assert_eq!(kippu.text, "きっぷ");
+        assert_eq!(
+            spans(&kippu),
+            vec![(0..2, 0..1), (2..3, 1..2), (3..5, 2..3)]
+        );
+
+        let tokyo = Converter::default().convert_aligned("tōkyō");
+        assert_eq!(spans(&tokyo), vec![(0..2, 0..2), (2..5, 2..5)]);
+
+        let macron = romaji().long_vowel(LongVowel::Macron).build();
+        let tokyo = macron.convert_aligned("とうきょう");
+        assert_eq!(tokyo.text, "tōkyō");
+        assert_eq!(spans(&tokyo), vec![(0..2, 0..2), (2..5, 2..5)]);
+
+        let konya = romaji().build().convert_aligned("コンヤ 2");
+        assert_eq!(
+            spans(&konya),
+            vec![
+                (0..1, 0..2),
+                (1..2, 2..4),
+                (2..3, 4..6),
+                (3..4, 6..7),
+                (4..5, 7..8)
+            ]
+        );
+        assert_eq!(
+            konya.from_input(1).map(|span| span.output.clone()),
+            Some(2..4)
+        );
+        assert_eq!(
+            konya.from_output(5).map(|span| span.input.clone()),
+            Some(2..3)
+        );
+
+        let hiragana = Converter::default().convert_aligned("ヷ");
+        assert_eq!(spans(&hiragana), vec![(0..1, 0..2)]);
+
+        let drop = Converter::builder().unknown(Unknown::Drop).build();
+        assert_eq!(
+            spans(&drop.convert_aligned("字ka")),
+            vec![(0..1, 0..0), (1..3, 0..1)]
+        );
+    }
+
+    fn fragment(text: &str, input: Range<usize>) -> Fragment {
+        Fragment {
+            text: text.to_string(),
+            input,
+        }
+    }
+
+    #[test]
+    fn test_strict_and_lenient() {
+        let converter = romaji().build();
+        assert_eq!(converter.try_convert("きっぷ"), Ok("kippu".to_string()));
+
+        let strict = romaji().dangling_sokuon(DanglingSokuon::Unknown).build();
+        let error = strict.try_convert("きっう ー").unwrap_err();
+        assert_eq!(
+            error,
+            Error::Unconvertible(vec![fragment("っ", 1..2), fragment("ー", 4..5)])
+        );
+        assert_eq!(
+            error.to_string(),
+            "Could not convert \"っ\" at 1..2, \"ー\" at 4..5"
+        );
+
+        let lenient = Converter::default().convert_lenient("kaq 漢字。");
+        assert_eq!(lenient.text, "かq 漢字。");
+        assert_eq!(
+            lenient.warnings,
+            vec![fragment("q", 2..3), fragment("漢字", 4..6)]
+        );
+
+        let drop = Converter::builder().unknown(Unknown::Drop).build();
+        assert_eq!(
+            drop.convert_lenient("字ka").warnings,
+            vec![fragment("字", 0..1)]
+        );
+    }
+
+    #[test]
+    fn test_strict_unmapped_kana() {
+        // Kana without a table entry fail for every target, not only romaji
+        for target in [Target::Hiragana, Target::Katakana, Target::Romaji] {
+            let strict = Converter::builder().target(target).build();
+
+            assert_eq!(
+                strict.try_convert("ピㇼカ"),
+                Err(Error::Unconvertible(vec![fragment("ㇼ", 1..2)]))
+            );
+            assert_eq!(
+                strict.try_convert("ヽ ヿ ゝ ゟ"),
+                Err(Error::Unconvertible(vec![
+                    fragment("ヽ", 0..1),
+                    fragment("ヿ", 2..3),
+                    fragment("ゝ", 4..5),
+                    fragment("ゟ", 6..7),
+                ]))
+            );
+        }
+
+        let hiragana = Converter::default();
+        assert_eq!(hiragana.try_convert("ヷ"), Ok("わ゛".to_string()));
+        assert_eq!(hiragana.convert("ピㇼカ"), "ぴㇼか");
+
+        let mut tables = Tables::builtin();
+        tables.add_ainu();
+        let ainu = Converter::builder().tables(tables).build();
+        assert_eq!(ainu.try_convert("ピㇼカ"), Ok("ぴㇼか".to_string()));
+    }
+
+    #[test]
+    fn test_shared_across_threads() {
+        let converter = Arc::new(romaji().build());
+
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let converter = Arc::clone(&converter);
+                thread::spawn(move || converter.convert("しんかんせん"))
+            })
+            .collect();
+
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), "shinkansen");
+        }
+    }
+}
@@ -0,0 +1,35 @@
+use crate::converter::Fragment;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    // The input mixes scripts, or is in a script that can't be converted (eg. kanji)
+    UnknownScript,
+    // A line of a custom table couldn't be read (see tables::Tables::load_tsv)
+    InvalidTable { line: usize, reason: String },
+    // A custom table file couldn't be opened or read
+    UnreadableTable(String),
+    // Parts of the input that couldn't be converted (see converter::Converter::try_convert)
+    Unconvertible(Vec<Fragment>),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::UnknownScript => write!(f, "Did not understand input character set."),
+            Error::InvalidTable { line, reason } => {
+                write!(f, "Invalid table at line {}: {}", line, reason)
+            }
+            Error::UnreadableTable(reason) => write!(f, "Could not read table: {}", reason),
+            Error::Unconvertible(fragments) => {
+                let fragments: Vec<String> = fragments
+                    .iter()
+                    .map(|fragment| format!("{:?} at {:?}", fragment.text, fragment.input))
+                    .collect();
+                write!(f, "Could not convert {}", fragments.join(", "))
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
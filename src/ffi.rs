@@ -0,0 +1,156 @@
+// C ABI, built with `cargo build --features ffi`. build.rs generates the header into
+// OUT_DIR, and tests/ffi.rs checks the copy in include/konj.h against it.
+//
+// Every function returns a status code. Converted strings are written to `output` as
+// NUL-terminated UTF-8 that the caller must release with `konj_string_free`.
+use crate::error::Error;
+use crate::lexer::LexItem;
+use crate::script;
+use std::ffi::{CStr, CString};
+use std::os::raw::c_char;
+use std::ptr;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Ok = 0,
+    NullPointer = 1,
+    InvalidUtf8 = 2,
+    UnknownScript = 3,
+    InvalidTable = 4,
+    Unconvertible = 5,
+}
+
+// Script breakdown of an input; `counts` and `percentages` are indexed by LexItem
+// (KONJ_LEX_ITEM_OTHER + 1 entries)
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct Script {
+    pub total: usize,
+    pub counts: [usize; 8],
+    pub percentages: [f64; 8],
+    // Only meaningful when `is_neutral` is false
+    pub dominant: LexItem,
+    pub is_neutral: bool,
+    pub is_mixed: bool,
+}
+
+/// # Safety
+///
+/// `input` must be NULL or a NUL-terminated string, `output` must be NULL or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn konj_to_hiragana(
+    input: *const c_char,
+    output: *mut *mut c_char,
+) -> Status {
+    convert(input, output, crate::try_to_hiragana)
+}
+
+/// # Safety
+///
+/// `input` must be NULL or a NUL-terminated string, `output` must be NULL or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn konj_to_katakana(
+    input: *const c_char,
+    output: *mut *mut c_char,
+) -> Status {
+    convert(input, output, crate::try_to_katakana)
+}
+
+/// # Safety
+///
+/// `input` must be NULL or a NUL-terminated string, `output` must be NULL or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn konj_to_romaji(input: *const c_char, output: *mut *mut c_char) -> Status {
+    convert(input, output, crate::try_to_romaji)
+}
+
+/// # Safety
+///
+/// `input` must be NULL or a NUL-terminated string, `output` must be NULL or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn konj_detect_script(input: *const c_char, output: *mut Script) -> Status {
+    if output.is_null() {
+        return Status::NullPointer;
+    }
+
+    let input = match read(input) {
+        Ok(input) => input,
+        Err(status) => return status,
+    };
+
+    let detected = script::detect(input);
+    let mut result = Script {
+        total: detected.total(),
+        counts: [0; 8],
+        percentages: [0.0; 8],
+        dominant: detected.dominant().unwrap_or(LexItem::Other),
+        is_neutral: detected.is_neutral(),
+        is_mixed: detected.is_mixed(),
+    };
+
+    for item in LexItem::ALL.iter() {
+        result.counts[*item as usize] = detected.count(*item);
+        result.percentages[*item as usize] = detected.percentage(*item);
+    }
+
+    *output = result;
+    Status::Ok
+}
+
+/// # Safety
+///
+/// `string` must be NULL or a pointer returned by one of the konj_to_* functions,
+/// and must not be used after this call.
+#[no_mangle]
+pub unsafe extern "C" fn konj_string_free(string: *mut c_char) {
+    if !string.is_null() {
+        drop(CString::from_raw(string));
+    }
+}
+
+unsafe fn convert(
+    input: *const c_char,
+    output: *mut *mut c_char,
+    f: fn(&str) -> Result<String, Error>,
+) -> Status {
+    if output.is_null() {
+        return Status::NullPointer;
+    }
+
+    *output = ptr::null_mut();
+
+    let input = match read(input) {
+        Ok(input) => input,
+        Err(status) => return status,
+    };
+
+    match f(input) {
+        Ok(converted) => {
+            // The input is NUL-terminated so the output can't contain NUL
+            *output = CString::new(converted).unwrap().into_raw();
+            Status::Ok
+        }
+        Err(error) => error.into(),
+    }
+}
+
+unsafe fn read<'a>(input: *const c_char) -> Result<&'a str, Status> {
+    if input.is_null() {
+        return Err(Status::NullPointer);
+    }
+
+    CStr::from_ptr(input)
+        .to_str()
+        .map_err(|_| Status::InvalidUtf8)
+}
+
+impl From<Error> for Status {
+    fn from(error: Error) -> Self {
+        match error {
+            Error::UnknownScript => Status::UnknownScript,
+            Error::InvalidTable { .. } | Error::UnreadableTable(_) => Status::InvalidTable,
+            Error::Unconvertible(_) => Status::Unconvertible,
+        }
+    }
+}
@@ -0,0 +1,142 @@
+use crate::converter::Target;
+use crate::kana::{self, DAKUTEN, HANDAKUTEN};
+use crate::{JIS_SHIFTED_TO_HIRAGANA, JIS_TO_HIRAGANA};
+
+// A physical key on a JIS keyboard, named by the unshifted character printed on it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Key {
+    pub code: char,
+    pub shift: bool,
+}
+
+impl Key {
+    pub fn new(code: char) -> Self {
+        Key { code, shift: false }
+    }
+
+    pub fn shifted(code: char) -> Self {
+        Key { code, shift: true }
+    }
+
+    // The kana this key types in kana input mode, if any
+    pub fn kana(self) -> Option<&'static str> {
+        let code = self.code.to_string();
+
+        if self.shift {
+            if let Some(kana) = JIS_SHIFTED_TO_HIRAGANA.get(code.as_str()) {
+                return Some(kana);
+            }
+        }
+
+        JIS_TO_HIRAGANA.get(code.as_str()).copied()
+    }
+}
+
+// Read a keystroke log typed on a JIS keyboard.
+//
+// Capital letters and the shifted ASCII symbols of the JIS layout ("#" for shift+3,
+// "<" for shift+",", "{" for shift+"[" etc.) are read as the shifted key. Keys with
+// no shifted kana type their unshifted one, so "!" is ぬ and "|" is ー.
+pub fn parse_keys(log: &str) -> Vec<Key> {
+    log.chars()
+        .map(|ch| match ch {
+            ch if ch.is_ascii_uppercase() => Key::shifted(ch.to_ascii_lowercase()),
+            '!' => Key::shifted('1'),
+            '"' => Key::shifted('2'),
+            '#' => Key::shifted('3'),
+            '$' => Key::shifted('4'),
+            '%' => Key::shifted('5'),
+            '&' => Key::shifted('6'),
+            '\'' => Key::shifted('7'),
+            '(' => Key::shifted('8'),
+            ')' => Key::shifted('9'),
+            '=' => Key::shifted('-'),
+            '~' => Key::shifted('^'),
+            '|' => Key::shifted('¥'),
+            '`' => Key::shifted('@'),
+            '+' => Key::shifted(';'),
+            '*' => Key::shifted(':'),
+            '<' => Key::shifted(','),
+            '>' => Key::shifted('.'),
+            '?' => Key::shifted('/'),
+            '{' => Key::shifted('['),
+            '}' => Key::shifted(']'),
+            '_' => Key::shifted('\\'),
+            ch => Key::new(ch),
+        })
+        .collect()
+}
+
+// Type the keys in kana mode.
+//
+// The ゛ and ゜ keys combine with the kana before them (か + ゛ → が), and are kept
+// as is when there is nothing to combine with. Keys without kana are passed through.
+pub fn to_hiragana(keys: &[Key]) -> String {
+    let mut result = String::new();
+
+    for key in keys {
+        match key.kana() {
+            Some(kana) => {
+                let mark = kana.chars().next();
+                let combined = match (mark, result.chars().last()) {
+                    (Some(DAKUTEN), Some(last)) => kana::voice(last),
+                    (Some(HANDAKUTEN), Some(last)) => kana::semi_voice(last),
+                    _ => None,
+                };
+
+                match combined {
+                    Some(combined) => {
+                        result.pop();
+                        result.push(combined);
+                    }
+                    None => result.push_str(kana),
+                }
+            }
+
+            None => result.push(key.code),
+        }
+    }
+
+    result
+}
+
+pub fn to_katakana(keys: &[Key]) -> String {
+    crate::converter(Target::Katakana).convert(&to_hiragana(keys))
+}
+
+pub fn to_romaji(keys: &[Key]) -> String {
+    crate::converter(Target::Romaji).convert(&to_hiragana(keys))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_kana() {
+        assert_eq!(Key::new('q').kana(), Some("た"));
+        assert_eq!(Key::new('w').kana(), Some("て"));
+        assert_eq!(Key::shifted('z').kana(), Some("っ"));
+        assert_eq!(Key::shifted('q').kana(), Some("た"));
+        assert_eq!(Key::new('\\').kana(), Some("ろ"));
+        assert_eq!(Key::new('`').kana(), None);
+    }
+
+    #[test]
+    fn test_convert() {
+        let sapporo = parse_keys("xZ-[\\");
+        assert_eq!(to_hiragana(&sapporo), "さっぽろ");
+        assert_eq!(to_katakana(&sapporo), "サッポロ");
+        assert_eq!(to_romaji(&sapporo), "sapporo");
+
+        let gakkou = parse_keys("t@Zb4");
+        assert_eq!(to_hiragana(&gakkou), "がっこう");
+        assert_eq!(to_romaji(&gakkou), "gakkou");
+
+        assert_eq!(to_hiragana(&parse_keys("g'<")), "きゃ、");
+        assert_eq!(to_hiragana(&parse_keys("@u@")), "゛な゛");
+        assert_eq!(to_hiragana(&parse_keys("!\"+*=~|`_")), "ぬふれけほへー゛ろ");
+        assert_eq!(to_katakana(&parse_keys("t`")), "ガ");
+        assert_eq!(to_romaji(&parse_keys("o|")), "raa");
+    }
+}
@@ -0,0 +1,182 @@
+use crate::converter::{Converter, Punctuation};
+use crate::{HENTAIGANA_TO_KANA, HIRAGANA_TO_KATAKANA, HIRAGANA_TO_ROMAJI, KATAKANA_TO_HIRAGANA};
+use std::sync::OnceLock;
+
+pub const CHOONPU: char = 'ー';
+pub const SOKUON: char = 'っ';
+pub const HATSUON: char = 'ん';
+pub const DAKUTEN: char = '゛';
+pub const HANDAKUTEN: char = '゜';
+
+// Small kana that attach to the previous kana instead of forming a mora of their own.
+// っ is small too, but is a mora by itself.
+const SMALL_GLIDES: [char; 9] = ['ぁ', 'ぃ', 'ぅ', 'ぇ', 'ぉ', 'ゃ', 'ゅ', 'ょ', 'ゎ'];
+
+// Small kana whose full-size form is the next code point (ゃ → や)
+const SMALL_KANA: &str = "ぁぃぅぇぉっゃゅょゎ";
+
+// Hiragana whose voiced form is the next code point (か → が)
+const VOICEABLE: &str = "かきくけこさしすせそたちつてとはひふへほ";
+
+// Hiragana whose semi-voiced form is two code points on (は → ぱ)
+const SEMI_VOICEABLE: &str = "はひふへほ";
+
+pub fn is_kana(ch: char) -> bool {
+    let mut buf = [0; 4];
+    let key: &str = ch.encode_utf8(&mut buf);
+
+    ch == CHOONPU
+        || HIRAGANA_TO_KATAKANA.contains_key(key)
+        || KATAKANA_TO_HIRAGANA.contains_key(key)
+        || HENTAIGANA_TO_KANA.contains_key(key)
+}
+
+pub fn is_small_glide(ch: char) -> bool {
+    SMALL_GLIDES.contains(&hiragana_char(ch))
+}
+
+// The hiragana for a single katakana char, or the char itself
+pub fn hiragana_char(ch: char) -> char {
+    let mut buf = [0; 4];
+    let key: &str = ch.encode_utf8(&mut buf);
+
+    KATAKANA_TO_HIRAGANA
+        .get(key)
+        .and_then(|hiragana| hiragana.chars().next())
+        .unwrap_or(ch)
+}
+
+// The hiragana with a dakuten added, eg. か → が
+pub fn voice(ch: char) -> Option<char> {
+    match ch {
+        'う' => Some('ゔ'),
+        ch if VOICEABLE.contains(ch) => std::char::from_u32(ch as u32 + 1),
+        _ => None,
+    }
+}
+
+// The hiragana with a handakuten added, eg. は → ぱ
+pub fn semi_voice(ch: char) -> Option<char> {
+    match ch {
+        ch if SEMI_VOICEABLE.contains(ch) => std::char::from_u32(ch as u32 + 2),
+        _ => None,
+    }
+}
+
+// The hiragana with its dakuten or handakuten removed, eg. が → か, ぱ → は
+pub fn unvoice(ch: char) -> char {
+    let before = |n: u32| (ch as u32).checked_sub(n).and_then(std::char::from_u32);
+
+    match ch {
+        'ゔ' => 'う',
+        _ => match (before(1), before(2)) {
+            (Some(base), _) if voice(base) == Some(ch) => base,
+            (_, Some(base)) if semi_voice(base) == Some(ch) => base,
+            _ => ch,
+        },
+    }
+}
+
+// The full-size form of a small hiragana, eg. ゃ → や, っ → つ
+pub fn to_large(ch: char) -> char {
+    match ch {
+        'ゕ' => 'か',
+        'ゖ' => 'け',
+        ch if SMALL_KANA.contains(ch) => std::char::from_u32(ch as u32 + 1).unwrap_or(ch),
+        _ => ch,
+    }
+}
+
+// The vowel a hiragana mora ends in, going by its romaji
+pub fn vowel(mora: &str) -> Option<char> {
+    HIRAGANA_TO_ROMAJI
+        .get(mora)
+        .and_then(|romaji| romaji.chars().last())
+        .filter(|ch| "aiueo".contains(*ch))
+}
+
+// The hiragana for a romaji vowel, eg. 'o' → お
+pub fn vowel_kana(vowel: char) -> Option<char> {
+    "aiueo"
+        .find(vowel)
+        .and_then(|index| "あいうえお".chars().nth(index))
+}
+
+// Hentaigana and archaic kana replaced with the modern kana they stand for
+pub fn from_hentaigana(input: &str) -> String {
+    input
+        .chars()
+        .map(|ch| {
+            let mut buf = [0; 4];
+            HENTAIGANA_TO_KANA
+                .get(ch.encode_utf8(&mut buf))
+                .map_or(ch.to_string(), |kana| kana.to_string())
+        })
+        .collect()
+}
+
+// Convert every romaji and katakana run in the input to hiragana, leaving
+// everything else (kanji, numbers, punctuation, spaces) as is.
+// Unlike `to_hiragana` this accepts mixed-script input, read by a Converter so
+// macrons are spelled out ("tōkyō" → とうきょう).
+pub fn normalize(input: &str) -> String {
+    static CONVERTER: OnceLock<Converter> = OnceLock::new();
+
+    CONVERTER
+        .get_or_init(|| Converter::builder().punctuation(Punctuation::Keep).build())
+        .convert(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize("けしゴム"), "けしごむ");
+        assert_eq!(normalize("kippu と 切符"), "きっぷ と 切符");
+        assert_eq!(normalize("らーめん"), "らーめん");
+        assert_eq!(normalize("\u{1B017}\u{1B002}"), "かあ");
+        assert_eq!(normalize("tōkyō と Kōbe!"), "とうきょう と こうべ!");
+    }
+
+    #[test]
+    fn test_from_hentaigana() {
+        assert_eq!(from_hentaigana("\u{1B002}\u{1B017}"), "あか");
+        assert_eq!(from_hentaigana("\u{1B000}\u{1B167}"), "エン");
+        assert_eq!(from_hentaigana("かな"), "かな");
+        assert!(is_kana('\u{1B002}'));
+    }
+
+    #[test]
+    fn test_voice() {
+        assert_eq!(voice('か'), Some('が'));
+        assert_eq!(voice('ほ'), Some('ぼ'));
+        assert_eq!(voice('う'), Some('ゔ'));
+        assert_eq!(voice('な'), None);
+        assert_eq!(semi_voice('ふ'), Some('ぷ'));
+        assert_eq!(semi_voice('か'), None);
+        assert_eq!(unvoice('が'), 'か');
+        assert_eq!(unvoice('ぱ'), 'は');
+        assert_eq!(unvoice('ば'), 'は');
+        assert_eq!(unvoice('ゔ'), 'う');
+        assert_eq!(unvoice('き'), 'き');
+        assert_eq!(unvoice('ひ'), 'ひ');
+    }
+
+    #[test]
+    fn test_to_large() {
+        assert_eq!(to_large('ゃ'), 'や');
+        assert_eq!(to_large('っ'), 'つ');
+        assert_eq!(to_large('ゖ'), 'け');
+        assert_eq!(to_large('か'), 'か');
+    }
+
+    #[test]
+    fn test_is_small_glide() {
+        assert!(is_small_glide('ょ'));
+        assert!(is_small_glide('ョ'));
+        assert!(!is_small_glide('っ'));
+        assert!(!is_small_glide('よ'));
+    }
+}
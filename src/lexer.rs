@@ -1,8 +1,13 @@
 use crate::constants::*;
+use crate::kana::CHOONPU;
 use crate::strings::*;
+use crate::HENTAIGANA_TO_KANA;
 use itertools::Itertools;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "wasm", wasm_bindgen::prelude::wasm_bindgen)]
+#[cfg_attr(feature = "python", pyo3::pyclass(eq, eq_int, hash, frozen))]
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum LexItem {
     Rom,
     Num,
@@ -10,26 +15,83 @@ pub enum LexItem {
     Hiragana,
     Katakana,
     Space,
+    Punct,
     Other,
 }
 
-fn parse(input: char) -> LexItem {
+impl LexItem {
+    pub const ALL: [LexItem; 8] = [
+        LexItem::Rom,
+        LexItem::Num,
+        LexItem::Kanji,
+        LexItem::Hiragana,
+        LexItem::Katakana,
+        LexItem::Space,
+        LexItem::Punct,
+        LexItem::Other,
+    ];
+}
+
+// Vowels with a macron or circumflex, which spell long vowels in romaji (tōkyō, tôkyô)
+const LONG_VOWEL_LETTERS: &str = "āīūēōâîûêôĀĪŪĒŌÂÎÛÊÔ";
+
+pub fn parse(input: char) -> LexItem {
     match input {
         input if is_char_between_char_range(input, KATAKANA_BEG, KATAKANA_END) => LexItem::Katakana,
+        input if is_char_between_char_range(input, KATAKANA_EXT_BEG, KATAKANA_EXT_END) => {
+            LexItem::Katakana
+        }
         input if is_char_between_char_range(input, HIRAGANA_BEG, HIRAGANA_END) => LexItem::Hiragana,
+        input if is_char_between_char_range(input, KANA_SUPPLEMENT_BEG, KANA_SUPPLEMENT_END) => {
+            hentaigana(input)
+        }
         input if is_char_between_char_range(input, KANJI_BEG, KANJI_END) => LexItem::Kanji,
         input if is_char_between_char_range(input, LATIN_NUM_BEG, LATIN_NUM_END) => LexItem::Num,
-        input if is_char_between_char_range(input, ROMAN_BEG, ROMAN_END) => LexItem::Rom,
-        JAPANESE_SPACE | SPACE => LexItem::Space,
+        input if is_char_between_char_range(input, LATIN_UPPER_BEG, LATIN_UPPER_END) => {
+            LexItem::Rom
+        }
+        input if is_char_between_char_range(input, LATIN_LOWER_BEG, LATIN_LOWER_END) => {
+            LexItem::Rom
+        }
+        input if LONG_VOWEL_LETTERS.contains(input) => LexItem::Rom,
+        input if is_punctuation(input) => LexItem::Punct,
+        input if input.is_whitespace() => LexItem::Space,
         _ => LexItem::Other,
     }
 }
 
-fn lex(input: &str) -> Result<Vec<String>, String> {
+// Hentaigana and archaic kana lex as the script of the kana they stand for
+fn hentaigana(input: char) -> LexItem {
+    let mut buf = [0; 4];
+
+    HENTAIGANA_TO_KANA
+        .get(input.encode_utf8(&mut buf))
+        .and_then(|kana| kana.chars().next())
+        .map_or(LexItem::Other, parse)
+}
+
+// Parse every char of the input. ー is used by hiragana and katakana alike,
+// so it takes the script of the kana before it (らーめん is all hiragana).
+pub fn parse_str(input: &str) -> Vec<LexItem> {
+    let mut items: Vec<LexItem> = Vec::new();
+
+    for ch in input.chars() {
+        let item = match (ch, items.last()) {
+            (CHOONPU, Some(LexItem::Hiragana)) => LexItem::Hiragana,
+            _ => parse(ch),
+        };
+        items.push(item);
+    }
+
+    items
+}
+
+pub fn lex(input: &str) -> Result<Vec<String>, String> {
     let mut result = Vec::new();
+    let items = parse_str(input);
 
-    for (_, group) in &input.chars().group_by(|c| parse(*c)) {
-        result.push(group.collect());
+    for (_, group) in &input.chars().zip(items).group_by(|(_, item)| *item) {
+        result.push(group.map(|(ch, _)| ch).collect());
     }
 
     Ok(result)
@@ -42,5 +104,30 @@ mod tests {
     #[test]
     fn test_lex() {
         assert_eq!(lex("けしゴム").unwrap(), vec!["けし", "ゴム"]);
+        assert_eq!(lex("こんじゅ が").unwrap(), vec!["こんじゅ", " ", "が"]);
+        assert_eq!(lex("kippu!!").unwrap(), vec!["kippu", "!!"]);
+        assert_eq!(lex("カムイ ピㇼカ").unwrap(), vec!["カムイ", " ", "ピㇼカ"]);
+        assert_eq!(
+            lex("らーめん ラーメン").unwrap(),
+            vec!["らーめん", " ", "ラーメン"]
+        );
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(parse(' '), LexItem::Space);
+        assert_eq!(parse(JAPANESE_SPACE), LexItem::Space);
+        assert_eq!(parse('!'), LexItem::Punct);
+        assert_eq!(parse('。'), LexItem::Punct);
+        assert_eq!(parse('？'), LexItem::Punct);
+        assert_eq!(parse('Ｋ'), LexItem::Other);
+        assert_eq!(parse('k'), LexItem::Rom);
+        assert_eq!(parse('ō'), LexItem::Rom);
+        assert_eq!(parse('Â'), LexItem::Rom);
+        assert_eq!(parse('7'), LexItem::Num);
+        assert_eq!(parse('ㇰ'), LexItem::Katakana);
+        assert_eq!(parse('\u{1B002}'), LexItem::Hiragana);
+        assert_eq!(parse('\u{1B000}'), LexItem::Katakana);
+        assert_eq!(parse('\u{1B123}'), LexItem::Other);
     }
 }
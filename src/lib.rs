@@ -1,101 +1,118 @@
 include!(concat!(env!("OUT_DIR"), "/data.rs"));
+pub mod collation;
+pub mod compare;
+pub mod composer;
 pub mod constants;
+pub mod converter;
+pub mod error;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod jis;
+pub mod kana;
 pub mod lexer;
+pub mod mora;
+pub mod orthography;
+#[cfg(feature = "python")]
+pub mod python;
+pub mod script;
+pub mod search;
 pub mod strings;
-
-use constants::*;
-use std::collections::{BTreeMap, HashMap};
+pub mod tables;
+pub mod verse;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+use converter::{Converter, Target};
+use error::Error;
+use lexer::LexItem;
 use std::string::String;
-use strings::*;
-
-fn to_hiragana(input: &str) -> String {
-    match (is_katakana(&input), is_hiragana(&input), is_romaji(&input)) {
-        (true, false, false) => repeatedly_replace_str_with_map(&input, &KATAKANA_TO_HIRAGANA),
+use std::sync::OnceLock;
 
-        (false, true, false) => String::from(input),
+pub fn to_hiragana(input: &str) -> String {
+    try_to_hiragana(input).unwrap_or_else(|error| error.to_string())
+}
 
-        (false, false, true) => {
-            let geminates = repeatedly_replace_str_with_map(&input, &GEMINATES_TO_HIRAGANA);
-            transform_input(&geminates, &ROMAJI_TO_HIRAGANA)
+pub fn try_to_hiragana(input: &str) -> Result<String, Error> {
+    let output = match (is_katakana(input), is_hiragana(input), is_romaji(input)) {
+        (true, false, false) | (false, true, false) | (false, false, true) => {
+            converter(Target::Hiragana).convert(input)
         }
 
-        (_, _, _) => format!("Did not understand input character set."),
-    }
+        (false, false, false) if is_neutral(input) => converter(Target::Hiragana).convert(input),
+
+        (_, _, _) => return Err(Error::UnknownScript),
+    };
+
+    Ok(output)
 }
 
-fn to_katakana(input: &str) -> String {
-    match (is_katakana(&input), is_hiragana(&input), is_romaji(&input)) {
-        (true, false, false) => String::from(input),
+pub fn to_katakana(input: &str) -> String {
+    try_to_katakana(input).unwrap_or_else(|error| error.to_string())
+}
 
-        (false, true, false) => {
-            strings::repeatedly_replace_str_with_map(&input, &HIRAGANA_TO_KATAKANA)
+pub fn try_to_katakana(input: &str) -> Result<String, Error> {
+    let output = match (is_katakana(input), is_hiragana(input), is_romaji(input)) {
+        (true, false, false) | (false, true, false) | (false, false, true) => {
+            converter(Target::Katakana).convert(input)
         }
 
-        (false, false, true) => {
-            let geminates = repeatedly_replace_str_with_map(&input, &GEMINATES_TO_HIRAGANA);
-            let hiragana_output = transform_input(&geminates, &ROMAJI_TO_HIRAGANA);
-            repeatedly_replace_str_with_map(&hiragana_output, &HIRAGANA_TO_KATAKANA)
-        }
+        (false, false, false) if is_neutral(input) => converter(Target::Katakana).convert(input),
 
-        (_, _, _) => format!("Did not understand input character set."),
-    }
-}
+        (_, _, _) => return Err(Error::UnknownScript),
+    };
 
-fn to_romaji(input: &str) -> String {
-    match (is_katakana(&input), is_hiragana(&input), is_romaji(&input)) {
-        (true, false, false) => {
-            let hiragana_output = repeatedly_replace_str_with_map(&input, &KATAKANA_TO_HIRAGANA);
+    Ok(output)
+}
 
-            repeatedly_replace_str_with_map(
-                &transform_input(&hiragana_output, &HIRAGANA_TO_ROMAJI),
-                &HIRAGANA_TO_GEMINATES,
-            )
-        }
+pub fn to_romaji(input: &str) -> String {
+    try_to_romaji(input).unwrap_or_else(|error| error.to_string())
+}
 
-        (false, true, false) => repeatedly_replace_str_with_map(
-            &transform_input(&input, &HIRAGANA_TO_ROMAJI),
-            &HIRAGANA_TO_GEMINATES,
-        ),
+pub fn try_to_romaji(input: &str) -> Result<String, Error> {
+    let output = match (is_katakana(input), is_hiragana(input), is_romaji(input)) {
+        // Kana goes through the converter for っ, ん and ー, which depend on the next kana
+        (true, false, false) | (false, true, false) => converter(Target::Romaji).convert(input),
 
         (false, false, true) => String::from(input),
 
-        (_, _, _) => format!("Did not understand input character set."),
-    }
+        (false, false, false) if is_neutral(input) => converter(Target::Romaji).convert(input),
+
+        (_, _, _) => return Err(Error::UnknownScript),
+    };
+
+    Ok(output)
 }
 
-fn transform_input(input: &str, map: &phf::Map<&str, &str>) -> String {
-    // BTreeMap to allow for a sorted lookup by key length
-    let mut group_by_key_size: BTreeMap<usize, HashMap<&str, &str>> = BTreeMap::new();
-    let mut result = String::from(input);
-
-    // Build the BTreeMap keyed by romaji key length
-    for (key, value) in map.into_iter() {
-        group_by_key_size
-            .entry(key.chars().count())
-            .or_insert_with(HashMap::new)
-            .insert(key, value);
-    }
+// The converters behind the free functions, so they agree with each other and
+// with Converter on っ, ん and ー
+pub(crate) fn converter(target: Target) -> &'static Converter {
+    static HIRAGANA: OnceLock<Converter> = OnceLock::new();
+    static KATAKANA: OnceLock<Converter> = OnceLock::new();
+    static ROMAJI: OnceLock<Converter> = OnceLock::new();
 
-    // Reverse the BTreeMap iterator to effectively start from the largest romaji key
-    for (_k, v) in group_by_key_size.iter().rev() {
-        for (key, value) in v {
-            result = result.replace(key, value);
-        }
-    }
+    let converter = match target {
+        Target::Hiragana => &HIRAGANA,
+        Target::Katakana => &KATAKANA,
+        Target::Romaji => &ROMAJI,
+    };
 
-    result
+    converter.get_or_init(|| Converter::builder().target(target).build())
 }
 
 fn is_katakana(s: &str) -> bool {
-    is_str_between_char_range(s, KATAKANA_BEG, KATAKANA_END)
+    script::detect(s).is_only(LexItem::Katakana)
 }
 
 fn is_hiragana(s: &str) -> bool {
-    is_str_between_char_range(s, HIRAGANA_BEG, HIRAGANA_END)
+    script::detect(s).is_only(LexItem::Hiragana)
 }
 
 fn is_romaji(s: &str) -> bool {
-    is_str_between_char_range(s, ROMAN_BEG, ROMAN_END)
+    script::detect(s).is_only(LexItem::Rom)
+}
+
+fn is_neutral(s: &str) -> bool {
+    script::detect(s).is_neutral()
 }
 
 #[cfg(test)]
@@ -108,6 +125,7 @@ mod tests {
         assert_eq!(to_hiragana("はは"), "はは");
         assert_eq!(to_hiragana("doki"), "どき");
         assert_eq!(to_hiragana("kippu"), "きっぷ");
+        assert_eq!(to_hiragana("tōkyō"), "とうきょう");
         assert_eq!(to_hiragana("きっう"), "きっう");
     }
 
@@ -127,11 +145,46 @@ mod tests {
         assert_eq!(to_romaji("ドキ"), "doki");
         assert_eq!(to_romaji("きっぷ"), "kippu");
         assert_eq!(to_romaji("キップ"), "kippu");
-        assert_eq!(to_romaji("きっう"), "kiっu");
+        assert_eq!(to_romaji("きっう"), "ki'u");
+        assert_eq!(to_romaji("えっ！"), "e'！");
+        assert_eq!(to_romaji("ッ"), "'");
+        assert_eq!(to_romaji("コーヒー"), "koohii");
+        assert_eq!(to_romaji("らーめん"), "raamen");
         assert_eq!(to_romaji("こんじゅ が すごい だ"), "konju ga sugoi da");
         assert_eq!(to_romaji("コンジュ ガ スゴイ ダ"), "konju ga sugoi da");
+        assert_eq!(to_hiragana(&to_romaji("こんや")), "こんや");
+        assert_eq!(to_katakana(&to_romaji("コンヤ")), "コンヤ");
         // assert_eq!(to_romaji("ばつげーむ"), "batsuge mu");
         // assert_eq!(to_romaji("抹げ む"), "抹ge mu");
         // assert_eq!(to_romaji("缶コーヒー"), "suupaa");
     }
+
+    #[test]
+    fn test_hentaigana_input() {
+        // あ, か and ち as hentaigana
+        let hentaigana = "\u{1B002}\u{1B017}\u{1B062}";
+        assert_eq!(to_hiragana(hentaigana), "あかち");
+        assert_eq!(to_katakana(hentaigana), "アカチ");
+        assert_eq!(to_romaji(hentaigana), "akachi");
+    }
+
+    #[test]
+    fn test_neutral_input() {
+        assert_eq!(to_hiragana("   "), "   ");
+        assert_eq!(to_katakana("123 !!"), "123 !!");
+        assert_eq!(to_romaji("123 !!"), "123 !!");
+        assert_eq!(to_romaji("。"), ".");
+        assert_eq!(to_hiragana("、"), "、");
+    }
+
+    #[test]
+    fn test_mixed_input() {
+        assert_eq!(
+            to_romaji("けしゴム"),
+            "Did not understand input character set."
+        );
+        assert_eq!(to_hiragana("kippu 2"), "きっぷ 2");
+        assert_eq!(try_to_katakana("けしゴム"), Err(Error::UnknownScript));
+        assert_eq!(try_to_katakana("けし"), Ok(String::from("ケシ")));
+    }
 }
@@ -0,0 +1,294 @@
+use crate::kana::{self, CHOONPU, HATSUON, SOKUON};
+use crate::{GEMINATES_TO_HIRAGANA, ROMAJI_TO_HIRAGANA};
+
+// A single romaji mora and what it was parsed as.
+//
+// `offset` is the char offset of the mora in the original input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mora {
+    pub romaji: String,
+    pub kind: MoraKind,
+    pub offset: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoraKind {
+    Kana(&'static str),
+    // The first letter of a doubled consonant, eg. the first "p" in "kippu"
+    Sokuon,
+    // Letters that do not start any known romaji
+    Invalid,
+}
+
+impl Mora {
+    pub fn is_valid(&self) -> bool {
+        self.kind != MoraKind::Invalid
+    }
+}
+
+// Split romaji into morae by longest match against ROMAJI_TO_HIRAGANA.
+//
+// Geminates (GEMINATES_TO_HIRAGANA, or any other doubled consonant) are checked
+// first, so "kippu" splits into "ki", "p" (sokuon), "pu", "beddo" into "be", "d"
+// (sokuon), "do" and "sampo" into "sa", "m" (ん), "po". Like the
+// converter, "n'" is ん and "nn" before a vowel or y is ん followed by a な-row
+// mora, so "konnichiwa" splits into "ko", "n", "ni", "chi", "wa".
+// Whitespace is skipped and consecutive unknown letters are grouped into a single invalid mora.
+pub fn segment(input: &str) -> Vec<Mora> {
+    let chars: Vec<char> = input.chars().map(|ch| ch.to_ascii_lowercase()).collect();
+    let max_key_len = ROMAJI_TO_HIRAGANA
+        .keys()
+        .map(|key| key.chars().count())
+        .max()
+        .unwrap_or(0);
+
+    let mut result: Vec<Mora> = Vec::new();
+    let mut pos = 0;
+
+    while pos < chars.len() {
+        if chars[pos].is_whitespace() {
+            pos += 1;
+            continue;
+        }
+
+        if let Some((mora, len)) = hatsuon_at(&chars, pos)
+            .or_else(|| geminate_at(&chars, pos))
+            .or_else(|| kana_at(&chars, pos, max_key_len))
+        {
+            result.push(mora);
+            pos += len;
+            continue;
+        }
+
+        match result.last_mut() {
+            Some(last)
+                if last.kind == MoraKind::Invalid
+                    && last.offset + last.romaji.chars().count() == pos =>
+            {
+                last.romaji.push(chars[pos])
+            }
+
+            _ => result.push(Mora {
+                romaji: chars[pos].to_string(),
+                kind: MoraKind::Invalid,
+                offset: pos,
+            }),
+        }
+
+        pos += 1;
+    }
+
+    result
+}
+
+// The letters that could not be parsed, with their char offsets
+pub fn invalid(input: &str) -> Vec<Mora> {
+    segment(input)
+        .into_iter()
+        .filter(|mora| !mora.is_valid())
+        .collect()
+}
+
+// Number of morae in hiragana, katakana or romaji input.
+//
+// っ, ん and ー are morae of their own, small ゃゅょ (and ぁぃぅぇぉゎ) are part of the
+// kana before them. Anything that isn't kana after conversion (kanji, punctuation) is not counted.
+pub fn count_morae(input: &str) -> usize {
+    kana_morae(&kana::normalize(input)).len()
+}
+
+// Number of syllables in hiragana, katakana or romaji input.
+//
+// A syllable is a mora plus an optional trailing っ, ん, ー, repeated vowel (とう, ねえ)
+// or second half of a diphthong (かい), so とうきょう is 4 morae but 2 syllables.
+pub fn count_syllables(input: &str) -> usize {
+    let mut syllables = 0;
+    let mut syllable_len = 0;
+    let mut vowel = None;
+
+    for mora in kana_morae(&kana::normalize(input)) {
+        let extends = match mora.chars().next() {
+            Some(SOKUON) | Some(HATSUON) | Some(CHOONPU) => syllable_len > 0,
+            _ => syllable_len == 1 && extends_vowel(vowel, kana::vowel(&mora)),
+        };
+
+        if extends {
+            syllable_len += 1;
+        } else {
+            syllables += 1;
+            syllable_len = 1;
+            vowel = kana::vowel(&mora);
+        }
+    }
+
+    syllables
+}
+
+// Group hiragana into morae, dropping anything that isn't kana
+fn kana_morae(hiragana: &str) -> Vec<String> {
+    let mut morae: Vec<String> = Vec::new();
+
+    for ch in hiragana.chars().filter(|ch| kana::is_kana(*ch)) {
+        match morae.last_mut() {
+            Some(last) if kana::is_small_glide(ch) => last.push(ch),
+            _ => morae.push(ch.to_string()),
+        }
+    }
+
+    morae
+}
+
+fn extends_vowel(previous: Option<char>, next: Option<char>) -> bool {
+    match (previous, next) {
+        (Some(previous), Some(next)) => {
+            previous == next || next == 'i' || (previous, next) == ('o', 'u')
+        }
+        _ => false,
+    }
+}
+
+// ん spelled "n'" (kan'i), or the first "n" of "nn" before a vowel or y (onna)
+fn hatsuon_at(chars: &[char], pos: usize) -> Option<(Mora, usize)> {
+    let len = match chars.get(pos..)? {
+        ['n', '\'', ..] => 2,
+        ['n', 'n', after, ..] if "aiueoy".contains(*after) => 1,
+        _ => return None,
+    };
+
+    Some((
+        Mora {
+            romaji: chars[pos..pos + len].iter().collect(),
+            kind: MoraKind::Kana("ん"),
+            offset: pos,
+        },
+        len,
+    ))
+}
+
+fn geminate_at(chars: &[char], pos: usize) -> Option<(Mora, usize)> {
+    let (first, second) = (*chars.get(pos)?, *chars.get(pos + 1)?);
+    let key: String = [first, second].iter().collect();
+
+    // The geminate only applies when the second letter starts a mora of its own
+    kana_at(chars, pos + 1, usize::MAX)?;
+
+    let kind = match GEMINATES_TO_HIRAGANA.get(key.as_str()) {
+        Some(partial) => match partial.chars().next()? {
+            'っ' => MoraKind::Sokuon,
+            first => MoraKind::Kana(&partial[..first.len_utf8()]),
+        },
+        // Any other doubled consonant ("dd", "gg") is a sokuon as well, like in the converter
+        None if first == second && first.is_ascii_alphabetic() && !"aiueon".contains(first) => {
+            MoraKind::Sokuon
+        }
+        None => return None,
+    };
+
+    Some((
+        Mora {
+            romaji: chars[pos].to_string(),
+            kind,
+            offset: pos,
+        },
+        1,
+    ))
+}
+
+fn kana_at(chars: &[char], pos: usize, max_key_len: usize) -> Option<(Mora, usize)> {
+    let longest = max_key_len.min(chars.len() - pos);
+
+    (1..=longest).rev().find_map(|len| {
+        let romaji: String = chars[pos..pos + len].iter().collect();
+
+        ROMAJI_TO_HIRAGANA.get(romaji.as_str()).map(|kana| {
+            (
+                Mora {
+                    romaji,
+                    kind: MoraKind::Kana(kana),
+                    offset: pos,
+                },
+                len,
+            )
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn romaji(input: &str) -> Vec<String> {
+        segment(input).into_iter().map(|mora| mora.romaji).collect()
+    }
+
+    #[test]
+    fn test_segment() {
+        assert_eq!(romaji("kippu"), vec!["ki", "p", "pu"]);
+        assert_eq!(romaji("shinkansen"), vec!["shi", "n", "ka", "n", "se", "n"]);
+        assert_eq!(romaji("sampo"), vec!["sa", "m", "po"]);
+        assert_eq!(romaji("kyou wa"), vec!["kyo", "u", "wa"]);
+
+        let kippu = segment("kippu");
+        assert_eq!(kippu[0].kind, MoraKind::Kana("き"));
+        assert_eq!(kippu[1].kind, MoraKind::Sokuon);
+        assert_eq!(kippu[2].kind, MoraKind::Kana("ぷ"));
+        assert_eq!(segment("sampo")[1].kind, MoraKind::Kana("ん"));
+    }
+
+    #[test]
+    fn test_segment_doubled_consonant() {
+        assert_eq!(romaji("beddo"), vec!["be", "d", "do"]);
+        assert_eq!(romaji("baggu"), vec!["ba", "g", "gu"]);
+        assert_eq!(romaji("hobbii"), vec!["ho", "b", "bi", "i"]);
+
+        for input in ["beddo", "baggu", "hobbii"] {
+            assert_eq!(segment(input)[1].kind, MoraKind::Sokuon);
+            assert!(invalid(input).is_empty());
+        }
+    }
+
+    #[test]
+    fn test_segment_hatsuon() {
+        assert_eq!(romaji("konnichiwa"), vec!["ko", "n", "ni", "chi", "wa"]);
+        assert_eq!(romaji("onna"), vec!["o", "n", "na"]);
+        assert_eq!(romaji("kan'i"), vec!["ka", "n'", "i"]);
+        assert!(invalid("kan'i konnichiwa").is_empty());
+        assert_eq!(segment("kan'i")[1].kind, MoraKind::Kana("ん"));
+    }
+
+    #[test]
+    fn test_segment_invalid() {
+        let morae = segment("kvvtsu");
+        assert_eq!(romaji("kvvtsu"), vec!["kvv", "tsu"]);
+        assert_eq!(morae[0].kind, MoraKind::Invalid);
+        assert_eq!(morae[1].offset, 3);
+
+        let bad = invalid("kakq toq");
+        assert_eq!(bad.len(), 2);
+        assert_eq!((bad[0].romaji.as_str(), bad[0].offset), ("kq", 2));
+        assert_eq!((bad[1].romaji.as_str(), bad[1].offset), ("q", 7));
+    }
+
+    #[test]
+    fn test_count_morae() {
+        assert_eq!(count_morae("とうきょう"), 4);
+        assert_eq!(count_morae("がっこう"), 4);
+        assert_eq!(count_morae("ラーメン"), 4);
+        assert_eq!(count_morae("shinkansen"), 6);
+        assert_eq!(count_morae("kippu"), 3);
+        assert_eq!(count_morae("ふぁん"), 2);
+        assert_eq!(count_morae("切符、きっぷ！"), 3);
+        assert_eq!(count_morae("tōkyō"), 4);
+    }
+
+    #[test]
+    fn test_count_syllables() {
+        assert_eq!(count_syllables("とうきょう"), 2);
+        assert_eq!(count_syllables("がっこう"), 2);
+        assert_eq!(count_syllables("ラーメン"), 2);
+        assert_eq!(count_syllables("shinkansen"), 3);
+        assert_eq!(count_syllables("おおい"), 2);
+        assert_eq!(count_syllables("かいしゃ"), 2);
+        assert_eq!(count_syllables("tōkyō"), 2);
+    }
+}
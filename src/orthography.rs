@@ -0,0 +1,123 @@
+use crate::kana;
+use crate::strings::is_punctuation;
+use crate::HIRAGANA_TO_KATAKANA;
+
+// Rows of the gojūon whose vowel changes in a vowel sequence, paired up by
+// consonant: かう → こう, きう → きゅう, けう → きょう
+const A_ROW: &str = "あかさたなはまやらわがざだばぱ";
+const O_ROW: &str = "おこそとのほもよろおごぞどぼぽ";
+const I_ROW: &str = "きしちにひみりぎじぢびぴ";
+const E_ROW: &str = "けせてねへめれげぜでべぺ";
+// The i-row kana an e-row kana is read with, spelled じ for ぢ as in
+// modern kana (でう → じょう). ぢ and づ are kept elsewhere: はなぢ, つづく
+const E_TO_I_ROW: &str = "きしちにひみりぎじじびぴ";
+
+const HA_ROW: &str = "はひふへほ";
+const WA_ROW: &str = "わいうえお";
+
+// Historical kana orthography (rekishiteki kanazukai) rewritten in the modern
+// one, keeping the script of every kana:
+//
+// けふ → きょう, てふてふ → ちょうちょう, ゐ → い, ゑ → え, かは → かわ
+//
+// The は-row reads as the わ-row inside a word only, so particles (は, へ) are
+// kept when they stand apart, as in "わたくし は". Kanji don't end a word.
+pub fn modernize(input: &str) -> String {
+    let chars: Vec<char> = input.chars().collect();
+    let hiragana: Vec<(usize, char)> = chars
+        .iter()
+        .map(|ch| kana::hiragana_char(*ch))
+        .enumerate()
+        .collect();
+
+    modernize_chars(&hiragana, false)
+        .into_iter()
+        .map(|(index, ch)| {
+            if chars[index] == hiragana[index].1 {
+                ch.to_string()
+            } else {
+                katakana(ch)
+            }
+        })
+        .collect()
+}
+
+// Modernize hiragana, paired with the index of the char it came from.
+// `medial` is whether the first char continues a word.
+pub(crate) fn modernize_chars(hiragana: &[(usize, char)], mut medial: bool) -> Vec<(usize, char)> {
+    let mut modern = Vec::with_capacity(hiragana.len());
+    let mut pos = 0;
+
+    while pos < hiragana.len() {
+        let (index, ch) = hiragana[pos];
+        let next = hiragana.get(pos + 1).map(|(_, next)| *next);
+
+        match (ch, next) {
+            // かう → こう, やう → よう
+            (ch, Some('う')) if A_ROW.contains(ch) => modern.push((index, row(ch, A_ROW, O_ROW))),
+            // いう → ゆう, きう → きゅう
+            ('い', Some('う')) => modern.push((index, 'ゆ')),
+            (ch, Some('う')) if I_ROW.contains(ch) => {
+                modern.push((index, row(ch, I_ROW, E_TO_I_ROW)));
+                modern.push((index, 'ゅ'));
+            }
+            // えう → よう, けう (けふ) → きょう
+            ('え', Some('う')) | ('え', Some('ふ')) => modern.push((index, 'よ')),
+            (ch, Some('う')) | (ch, Some('ふ')) if E_ROW.contains(ch) => {
+                modern.push((index, row(ch, E_ROW, E_TO_I_ROW)));
+                modern.push((index, 'ょ'));
+            }
+            // くゎ → か
+            ('く', Some('ゎ')) | ('ぐ', Some('ゎ')) => {
+                modern.push((index, if ch == 'く' { 'か' } else { 'が' }));
+                pos += 1;
+            }
+            ('ゐ', _) => modern.push((index, 'い')),
+            ('ゑ', _) => modern.push((index, 'え')),
+            (ch, _) if medial && HA_ROW.contains(ch) => {
+                modern.push((index, row(ch, HA_ROW, WA_ROW)))
+            }
+            (ch, _) => modern.push((index, ch)),
+        }
+
+        medial = !(ch.is_whitespace() || is_punctuation(ch));
+        pos += 1;
+    }
+
+    modern
+}
+
+// The kana in the same place of another row
+fn row(ch: char, from: &str, to: &str) -> char {
+    from.chars()
+        .position(|kana| kana == ch)
+        .and_then(|position| to.chars().nth(position))
+        .unwrap_or(ch)
+}
+
+fn katakana(ch: char) -> String {
+    let mut buf = [0; 4];
+
+    HIRAGANA_TO_KATAKANA
+        .get(ch.encode_utf8(&mut buf))
+        .map_or(ch.to_string(), |katakana| katakana.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_modernize() {
+        assert_eq!(modernize("けふ"), "きょう");
+        assert_eq!(modernize("てふてふ"), "ちょうちょう");
+        assert_eq!(modernize("ゐど ゑがほ"), "いど えがお");
+        assert_eq!(modernize("かは あはれ おほきい"), "かわ あわれ おおきい");
+        assert_eq!(modernize("やうやく せうせつ"), "ようやく しょうせつ");
+        assert_eq!(modernize("くゎじ"), "かじ");
+        assert_eq!(modernize("わたくし は いふ"), "わたくし は いう");
+        assert_eq!(modernize("思ふ"), "思う");
+        assert_eq!(modernize("ヰスキー テフ"), "イスキー チョウ");
+        assert_eq!(modernize("つづく はなぢ ちぢむ"), "つづく はなぢ ちぢむ");
+    }
+}
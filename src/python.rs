@@ -0,0 +1,118 @@
+// Python bindings, built with `maturin develop` (see pyproject.toml).
+//
+// The module mirrors the Rust API: to_hiragana, to_katakana and to_romaji return the
+// same strings as their Rust counterparts, while the try_ variants raise ValueError.
+use crate::error::Error;
+use crate::lexer::{self, LexItem};
+use crate::script;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+#[pyfunction]
+fn to_hiragana(input: &str) -> String {
+    crate::to_hiragana(input)
+}
+
+#[pyfunction]
+fn to_katakana(input: &str) -> String {
+    crate::to_katakana(input)
+}
+
+#[pyfunction]
+fn to_romaji(input: &str) -> String {
+    crate::to_romaji(input)
+}
+
+#[pyfunction]
+fn try_to_hiragana(input: &str) -> PyResult<String> {
+    crate::try_to_hiragana(input).map_err(value_error)
+}
+
+#[pyfunction]
+fn try_to_katakana(input: &str) -> PyResult<String> {
+    crate::try_to_katakana(input).map_err(value_error)
+}
+
+#[pyfunction]
+fn try_to_romaji(input: &str) -> PyResult<String> {
+    crate::try_to_romaji(input).map_err(value_error)
+}
+
+#[pyfunction]
+fn lex(input: &str) -> PyResult<Vec<String>> {
+    lexer::lex(input).map_err(PyValueError::new_err)
+}
+
+#[pyfunction]
+fn parse(input: char) -> LexItem {
+    lexer::parse(input)
+}
+
+#[pyfunction]
+fn detect(input: &str) -> Script {
+    Script(script::detect(input))
+}
+
+// script::Script, exposed to Python as konj.Script
+#[pyclass(frozen)]
+struct Script(script::Script);
+
+#[pymethods]
+impl Script {
+    fn count(&self, item: LexItem) -> usize {
+        self.0.count(item)
+    }
+
+    fn total(&self) -> usize {
+        self.0.total()
+    }
+
+    fn percentage(&self, item: LexItem) -> f64 {
+        self.0.percentage(item)
+    }
+
+    fn confidence(&self, item: LexItem) -> f64 {
+        self.0.confidence(item)
+    }
+
+    fn breakdown(&self) -> Vec<(LexItem, f64)> {
+        self.0.breakdown()
+    }
+
+    fn dominant(&self) -> Option<LexItem> {
+        self.0.dominant()
+    }
+
+    fn is_only(&self, item: LexItem) -> bool {
+        self.0.is_only(item)
+    }
+
+    fn is_neutral(&self) -> bool {
+        self.0.is_neutral()
+    }
+
+    fn is_mixed(&self) -> bool {
+        self.0.is_mixed()
+    }
+}
+
+fn value_error(error: Error) -> PyErr {
+    PyValueError::new_err(error.to_string())
+}
+
+#[pymodule]
+fn konj(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(to_hiragana, m)?)?;
+    m.add_function(wrap_pyfunction!(to_katakana, m)?)?;
+    m.add_function(wrap_pyfunction!(to_romaji, m)?)?;
+    m.add_function(wrap_pyfunction!(try_to_hiragana, m)?)?;
+    m.add_function(wrap_pyfunction!(try_to_katakana, m)?)?;
+    m.add_function(wrap_pyfunction!(try_to_romaji, m)?)?;
+    m.add_function(wrap_pyfunction!(lex, m)?)?;
+    m.add_function(wrap_pyfunction!(parse, m)?)?;
+    m.add_function(wrap_pyfunction!(detect, m)?)?;
+    m.add_class::<LexItem>()?;
+    m.add_class::<Script>()?;
+
+    Ok(())
+}
@@ -0,0 +1,151 @@
+use crate::lexer::{parse_str, LexItem};
+use std::collections::BTreeMap;
+
+// A breakdown of which scripts make up a piece of text.
+//
+// Spaces, numbers and punctuation are counted but treated as neutral:
+// they never decide which script an input is written in.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Script {
+    counts: BTreeMap<LexItem, usize>,
+    total: usize,
+}
+
+pub fn detect(input: &str) -> Script {
+    let mut script = Script::default();
+
+    for item in parse_str(input) {
+        *script.counts.entry(item).or_default() += 1;
+        script.total += 1;
+    }
+
+    script
+}
+
+pub fn is_neutral_item(item: LexItem) -> bool {
+    matches!(item, LexItem::Num | LexItem::Space | LexItem::Punct)
+}
+
+impl Script {
+    pub fn count(&self, item: LexItem) -> usize {
+        self.counts.get(&item).copied().unwrap_or(0)
+    }
+
+    pub fn total(&self) -> usize {
+        self.total
+    }
+
+    // Share of all characters, from 0.0 to 100.0
+    pub fn percentage(&self, item: LexItem) -> f64 {
+        percent(self.count(item), self.total)
+    }
+
+    // Share of the non-neutral characters, from 0.0 to 100.0
+    pub fn confidence(&self, item: LexItem) -> f64 {
+        if is_neutral_item(item) {
+            return 0.0;
+        }
+
+        percent(self.count(item), self.script_total())
+    }
+
+    // Every item present in the input with its percentage, largest first
+    pub fn breakdown(&self) -> Vec<(LexItem, f64)> {
+        let mut breakdown: Vec<(LexItem, f64)> = self
+            .counts
+            .keys()
+            .map(|&item| (item, self.percentage(item)))
+            .collect();
+
+        breakdown.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap().then(a.0.cmp(&b.0)));
+        breakdown
+    }
+
+    // The most common non-neutral item, if there is one
+    pub fn dominant(&self) -> Option<LexItem> {
+        self.counts
+            .iter()
+            .filter(|(item, _)| !is_neutral_item(**item))
+            .max_by(|a, b| a.1.cmp(b.1).then(b.0.cmp(a.0)))
+            .map(|(item, _)| *item)
+    }
+
+    // True when `item` is the only non-neutral item in the input
+    pub fn is_only(&self, item: LexItem) -> bool {
+        self.count(item) > 0 && self.count(item) == self.script_total()
+    }
+
+    // True when the input has no script characters at all (empty, whitespace, numbers, punctuation)
+    pub fn is_neutral(&self) -> bool {
+        self.script_total() == 0
+    }
+
+    pub fn is_mixed(&self) -> bool {
+        self.counts
+            .keys()
+            .filter(|item| !is_neutral_item(**item))
+            .count()
+            > 1
+    }
+
+    fn script_total(&self) -> usize {
+        self.counts
+            .iter()
+            .filter(|(item, _)| !is_neutral_item(**item))
+            .map(|(_, count)| count)
+            .sum()
+    }
+}
+
+fn percent(count: usize, total: usize) -> f64 {
+    if total == 0 {
+        return 0.0;
+    }
+
+    count as f64 * 100.0 / total as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect() {
+        let script = detect("きっぷ");
+        assert!(script.is_only(LexItem::Hiragana));
+        assert!(detect("らーめん").is_only(LexItem::Hiragana));
+        assert!(detect("ラーメン").is_only(LexItem::Katakana));
+        assert_eq!(script.dominant(), Some(LexItem::Hiragana));
+
+        let script = detect("kippu, please!");
+        assert!(script.is_only(LexItem::Rom));
+        assert_eq!(script.confidence(LexItem::Rom), 100.0);
+    }
+
+    #[test]
+    fn test_detect_neutral() {
+        assert!(detect("").is_neutral());
+        assert!(detect("   ").is_neutral());
+        assert!(detect("123 !!").is_neutral());
+        assert!(!detect("123 !!").is_only(LexItem::Rom));
+        assert!(!detect("  ").is_only(LexItem::Hiragana));
+        assert_eq!(detect("123 !!").dominant(), None);
+    }
+
+    #[test]
+    fn test_detect_mixed() {
+        let script = detect("けしゴム");
+        assert!(script.is_mixed());
+        assert_eq!(script.percentage(LexItem::Hiragana), 50.0);
+        assert_eq!(script.percentage(LexItem::Katakana), 50.0);
+        assert_eq!(
+            detect("ab か").breakdown(),
+            vec![
+                (LexItem::Rom, 50.0),
+                (LexItem::Hiragana, 25.0),
+                (LexItem::Space, 25.0)
+            ]
+        );
+        assert_eq!(detect("ab か").dominant(), Some(LexItem::Rom));
+    }
+}
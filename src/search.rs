@@ -0,0 +1,236 @@
+use crate::compare::{self, FoldOptions};
+use crate::composer::RomajiComposer;
+use crate::converter::Converter;
+use crate::kana::{self, CHOONPU};
+use crate::{GEMINATES_TO_HIRAGANA, ROMAJI_TO_HIRAGANA};
+use std::sync::OnceLock;
+
+// Where a query matched, as char offsets into the haystack (end is exclusive)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Match {
+    pub start: usize,
+    pub end: usize,
+}
+
+// Find a romaji query in a kana haystack.
+//
+// Every romaji alias for a kana is equivalent ("shi", "si" and "ci" all match し,
+// "n", "nn" and "xn" all match ん), hiragana and katakana are interchangeable, and
+// long vowels, ー and whitespace are ignored, so "sinkannsenn" finds "シンカンセン".
+// Hepburn "nn" before a vowel is ん followed by a な-row mora, so "konnichiha"
+// finds "こんにちは" as well as the typed "konnnichiha".
+pub fn find(query: &str, haystack: &str) -> Option<Match> {
+    search(query, haystack, false, false)
+}
+
+// Like `find`, but the query may end in partially typed romaji: "shinkans" finds "しんかんせん"
+pub fn find_prefix(query: &str, haystack: &str) -> Option<Match> {
+    search(query, haystack, true, false)
+}
+
+pub fn is_match(query: &str, haystack: &str) -> bool {
+    find(query, haystack).is_some()
+}
+
+// Autocomplete: true when the haystack starts with the (possibly partially typed) query
+pub fn starts_with(query: &str, haystack: &str) -> bool {
+    search(query, haystack, true, true).is_some()
+}
+
+fn search(query: &str, haystack: &str, partial: bool, anchored: bool) -> Option<Match> {
+    let query = query.replace(char::is_whitespace, "");
+    let mut composer = RomajiComposer::new();
+    composer.push_str(&query);
+
+    if !partial {
+        composer.flush();
+    }
+
+    // The query is read both as typed into an IME ("konnnichiha") and as written
+    // ("konnichiha", where "nn" before a vowel is ん followed by a な-row mora)
+    let pending = composer.pending();
+    let typed: String = query
+        .chars()
+        .take(query.chars().count() - pending.chars().count())
+        .collect();
+    let written = hiragana_converter().convert(&typed);
+
+    let hay = fold_haystack(haystack);
+
+    [composer.committed(), written.as_str()]
+        .iter()
+        .filter_map(|committed| search_folded(committed, pending, haystack, &hay, anchored))
+        .min_by_key(|found| found.start)
+}
+
+fn search_folded(
+    committed: &str,
+    pending: &str,
+    haystack: &str,
+    hay: &[(char, usize)],
+    anchored: bool,
+) -> Option<Match> {
+    let options = FoldOptions {
+        ignore_long_vowels: true,
+        ..FoldOptions::default()
+    };
+    let needle: Vec<char> = compare::fold(committed, options).chars().collect();
+
+    let last_start = if anchored { 0 } else { hay.len() };
+    let offset = |i: usize| {
+        hay.get(i)
+            .map_or(haystack.chars().count(), |(_, index)| *index)
+    };
+
+    (0..=last_start).find_map(|start| {
+        let candidate = hay.get(start..start + needle.len())?;
+
+        if !candidate.iter().map(|(ch, _)| ch).eq(needle.iter()) {
+            return None;
+        }
+
+        let matched = needle.len() + pending_len(pending, &hay[start + needle.len()..])?;
+        let end = match matched {
+            0 => offset(start),
+            _ => hay[start + matched - 1].1 + 1,
+        };
+
+        Some(Match {
+            start: offset(start),
+            end,
+        })
+    })
+}
+
+// Converters are built once, building their tables per search is slow
+fn hiragana_converter() -> &'static Converter {
+    static CONVERTER: OnceLock<Converter> = OnceLock::new();
+
+    CONVERTER.get_or_init(Converter::default)
+}
+
+// How many haystack chars the partially typed romaji covers, if it can match at all
+fn pending_len(pending: &str, hay: &[(char, usize)]) -> Option<usize> {
+    if pending.is_empty() {
+        return Some(0);
+    }
+
+    let starts_with = |kana: &str| {
+        let kana: Vec<char> = kana.chars().filter(|ch| *ch != CHOONPU).collect();
+        let matches = !kana.is_empty()
+            && hay
+                .iter()
+                .map(|(ch, _)| ch)
+                .take(kana.len())
+                .eq(kana.iter());
+
+        if matches {
+            Some(kana.len())
+        } else {
+            None
+        }
+    };
+
+    // A consonant that could be the first half of a geminate matches っ
+    let sokuon = GEMINATES_TO_HIRAGANA
+        .entries()
+        .filter(|(romaji, partial)| romaji.starts_with(pending) && partial.starts_with('っ'))
+        .find_map(|_| starts_with("っ"));
+
+    ROMAJI_TO_HIRAGANA
+        .entries()
+        .filter(|(romaji, _)| romaji.starts_with(pending))
+        .filter_map(|(_, kana)| starts_with(kana))
+        .chain(sokuon)
+        .max()
+}
+
+// Hiragana for every char of the haystack, paired with its char offset.
+// ー, vowels that only lengthen the previous one and whitespace are dropped.
+fn fold_haystack(haystack: &str) -> Vec<(char, usize)> {
+    let mut result = Vec::new();
+    let mut vowel = None;
+
+    for (index, ch) in haystack.chars().enumerate() {
+        let ch = kana::hiragana_char(ch.to_ascii_lowercase());
+
+        if ch.is_whitespace() || ch == CHOONPU || compare::is_long_vowel(vowel, ch) {
+            continue;
+        }
+
+        if let Some(next) = kana::vowel(&ch.to_string()) {
+            vowel = Some(next);
+        } else if !kana::is_small_glide(ch) {
+            vowel = None;
+        }
+
+        result.push((ch, index));
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find() {
+        let shinkansen = Some(Match { start: 0, end: 6 });
+        assert_eq!(find("shinkansen", "しんかんせん"), shinkansen);
+        assert_eq!(find("sinkansen", "シンカンセン"), shinkansen);
+        assert_eq!(find("shinkannsenn", "しんかんせん"), shinkansen);
+        assert_eq!(
+            find("shinkansen", "とうかいどう しんかんせん"),
+            Some(Match { start: 7, end: 13 })
+        );
+        assert_eq!(find("ramen", "ラーメン"), Some(Match { start: 0, end: 4 }));
+        assert_eq!(
+            find("tokyo", "東京 とうきょう"),
+            Some(Match { start: 3, end: 7 })
+        );
+        assert_eq!(find("shinkans", "しんかんせん"), None);
+        assert!(!is_match("kyoto", "とうきょう"));
+    }
+
+    #[test]
+    fn test_find_hepburn_n() {
+        assert_eq!(
+            find("konnichiha", "こんにちは"),
+            Some(Match { start: 0, end: 5 })
+        );
+        assert_eq!(find("onna", "おんな"), Some(Match { start: 0, end: 3 }));
+        assert_eq!(find("minna", "みんな"), Some(Match { start: 0, end: 3 }));
+        assert_eq!(
+            find("konnnichiha", "こんにちは"),
+            Some(Match { start: 0, end: 5 })
+        );
+        assert_eq!(find("kon'ya", "コンヤ"), Some(Match { start: 0, end: 3 }));
+        assert!(starts_with("konnichi", "こんにちは"));
+    }
+
+    #[test]
+    fn test_find_prefix() {
+        assert_eq!(
+            find_prefix("shinkans", "しんかんせん"),
+            Some(Match { start: 0, end: 5 })
+        );
+        assert_eq!(
+            find_prefix("kip", "きっぷ"),
+            Some(Match { start: 0, end: 2 })
+        );
+        assert_eq!(
+            find_prefix("shinkan", "しんかんせん"),
+            Some(Match { start: 0, end: 4 })
+        );
+        assert_eq!(find_prefix("kanz", "しんかんせん"), None);
+    }
+
+    #[test]
+    fn test_starts_with() {
+        assert!(starts_with("shinka", "しんかんせん"));
+        assert!(starts_with("sinkans", "シンカンセン"));
+        assert!(!starts_with("kansen", "しんかんせん"));
+        assert!(starts_with("", "しんかんせん"));
+    }
+}
@@ -1,27 +1,62 @@
+use crate::constants::*;
+use itertools::Itertools;
 use phf::Map;
+use std::cmp::Reverse;
 use std::string::String;
 
+// True when every char is in the range. Whitespace is not in any range unless
+// the range says so, and an empty string belongs to none.
 pub fn is_str_between_char_range(s: &str, range_beg: char, range_end: char) -> bool {
-    for ch in s.trim().chars() {
-        if !is_char_between_char_range(ch, range_beg, range_end) {
-            return false;
-        }
-    }
-
-    true
+    !s.is_empty()
+        && s.chars()
+            .all(|ch| is_char_between_char_range(ch, range_beg, range_end))
 }
 
 pub fn is_char_between_char_range(ch: char, range_beg: char, range_end: char) -> bool {
-    if !(ch >= range_beg && ch <= range_end || ch.is_whitespace()) {
-        return false;
-    }
+    ch >= range_beg && ch <= range_end
+}
 
-    true
+// ASCII punctuation, CJK punctuation (、。「」 etc.) and the fullwidth forms of ASCII punctuation
+pub fn is_punctuation(ch: char) -> bool {
+    match ch {
+        ch if ch.is_ascii_punctuation() => true,
+        ch if is_char_between_char_range(ch, CJK_PUNCT_BEG, CJK_PUNCT_END) => true,
+        ch if is_char_between_char_range(ch, FULLWIDTH_BEG, FULLWIDTH_END) => {
+            std::char::from_u32(ch as u32 - FULLWIDTH_OFFSET)
+                .is_some_and(|half| half.is_ascii_punctuation())
+        }
+        _ => false,
+    }
 }
 
+// Longest keys are replaced first, so that eg. "ゐ゛" wins over "ゐ"
 pub fn repeatedly_replace_str_with_map(s: &str, map: &Map<&str, &str>) -> String {
-    map.into_iter().fold(String::from(s), |mut acc, (k, v)| {
-        acc = acc.replace(k, v);
-        acc
-    })
+    map.entries()
+        .sorted_by_key(|(k, _)| Reverse(k.chars().count()))
+        .fold(String::from(s), |mut acc, (k, v)| {
+            acc = acc.replace(k, v);
+            acc
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_str_between_char_range() {
+        assert!(is_str_between_char_range("kippu", ROMAN_BEG, ROMAN_END));
+        assert!(!is_str_between_char_range("123 !!", ROMAN_BEG, ROMAN_END));
+        assert!(!is_str_between_char_range(
+            "   ",
+            HIRAGANA_BEG,
+            HIRAGANA_END
+        ));
+        assert!(!is_str_between_char_range("", HIRAGANA_BEG, HIRAGANA_END));
+        assert!(is_str_between_char_range(
+            "かな",
+            HIRAGANA_BEG,
+            HIRAGANA_END
+        ));
+    }
 }
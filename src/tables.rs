@@ -0,0 +1,357 @@
+use crate::error::Error;
+use crate::{
+    AINU_TO_KATAKANA, GEMINATES_TO_HIRAGANA, HENTAIGANA_TO_KANA, HIRAGANA_TO_KATAKANA,
+    HIRAGANA_TO_ROMAJI, KATAKANA_TO_AINU, KATAKANA_TO_HIRAGANA, ROMAJI_TO_HIRAGANA,
+};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+// Which data a mapping belongs to, in the same shape as the build.rs data:
+//
+// Romaji: romaji → kana, eg. ("ka", "か")
+// Geminates: romaji → partial kana, eg. ("kk", "っk")
+// Kana: katakana → hiragana, eg. ("カ", "か")
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Table {
+    Romaji,
+    Geminates,
+    Kana,
+}
+
+// Conversion tables built at runtime, for use with a Converter.
+//
+// Start from the built-in data and layer mappings on top, or start empty (or
+// `clear` a table) to replace it entirely:
+//
+// let mut tables = Tables::builtin();
+// tables.alias(Table::Romaji, "tsy", "つ");
+// tables.load_tsv(Table::Romaji, "kwa\tくゎ\n")?;
+// Converter::builder().tables(tables).build()
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tables {
+    pub(crate) romaji_to_hiragana: HashMap<String, String>,
+    pub(crate) geminates_to_hiragana: HashMap<String, String>,
+    pub(crate) hiragana_to_romaji: HashMap<String, String>,
+    pub(crate) hiragana_to_katakana: HashMap<String, String>,
+    pub(crate) katakana_to_hiragana: HashMap<String, String>,
+    // Syllable-final consonants, read where no romaji matches: the consonant, or
+    // the vowel before it and the consonant ("ar" → ㇻ)
+    pub(crate) finals: HashMap<String, String>,
+}
+
+impl Default for Tables {
+    fn default() -> Self {
+        Self::builtin()
+    }
+}
+
+impl Tables {
+    pub fn builtin() -> Self {
+        let mut tables = Tables {
+            romaji_to_hiragana: to_owned(&ROMAJI_TO_HIRAGANA),
+            geminates_to_hiragana: to_owned(&GEMINATES_TO_HIRAGANA),
+            hiragana_to_romaji: to_owned(&HIRAGANA_TO_ROMAJI),
+            hiragana_to_katakana: to_owned(&HIRAGANA_TO_KATAKANA),
+            katakana_to_hiragana: to_owned(&KATAKANA_TO_HIRAGANA),
+            finals: HashMap::new(),
+        };
+
+        // A few romaji map to katakana (ヴ, ヵ, ヶ), keep everything hiragana
+        // so that kana → kana conversion stays char-by-char
+        tables.romaji_to_hiragana = tables
+            .romaji_to_hiragana
+            .iter()
+            .map(|(romaji, kana)| (romaji.clone(), tables.hiragana(kana)))
+            .collect();
+        tables.hiragana_to_romaji = tables
+            .hiragana_to_romaji
+            .iter()
+            .map(|(kana, romaji)| (tables.hiragana(kana), romaji.clone()))
+            .collect();
+
+        // Hentaigana are read as the modern kana, but never written
+        for (hentaigana, kana) in HENTAIGANA_TO_KANA.entries() {
+            let hiragana = tables.hiragana(kana);
+            tables.alias(Table::Kana, hentaigana, &hiragana);
+        }
+
+        tables
+    }
+
+    pub fn empty() -> Self {
+        Tables {
+            romaji_to_hiragana: HashMap::new(),
+            geminates_to_hiragana: HashMap::new(),
+            hiragana_to_romaji: HashMap::new(),
+            hiragana_to_katakana: HashMap::new(),
+            katakana_to_hiragana: HashMap::new(),
+            finals: HashMap::new(),
+        }
+    }
+
+    // Add the Ainu small katakana (data/ainu.tsv), read from and written as
+    // syllable-final consonants: ピㇼカ ↔ "pirka", カㇺ ↔ "kam". The glides y and
+    // w are only read, as イ and ウ (aynu → アイヌ).
+    pub fn add_ainu(&mut self) {
+        self.finals.extend(to_owned(&AINU_TO_KATAKANA));
+        self.hiragana_to_romaji.extend(to_owned(&KATAKANA_TO_AINU));
+    }
+
+    // Add a mapping in both directions, replacing any existing one. Like the
+    // "dominant" build.rs data, `from` becomes the preferred spelling of `to`.
+    pub fn insert(&mut self, table: Table, from: &str, to: &str) {
+        self.alias(table, from, to);
+
+        match table {
+            Table::Romaji => {
+                let kana = self.hiragana(to);
+                self.hiragana_to_romaji.insert(kana, from.to_string());
+            }
+            Table::Geminates => {}
+            Table::Kana => {
+                self.hiragana_to_katakana
+                    .insert(to.to_string(), from.to_string());
+            }
+        }
+    }
+
+    // Add a one-way mapping that is read but never written, like the "rest"
+    // build.rs data, eg. ("si", "し") while し is still written as "shi"
+    pub fn alias(&mut self, table: Table, from: &str, to: &str) {
+        match table {
+            Table::Romaji => {
+                let kana = self.hiragana(to);
+                self.romaji_to_hiragana
+                    .insert(from.to_ascii_lowercase(), kana);
+            }
+            Table::Geminates => {
+                self.geminates_to_hiragana
+                    .insert(from.to_ascii_lowercase(), to.to_string());
+            }
+            Table::Kana => {
+                self.katakana_to_hiragana
+                    .insert(from.to_string(), to.to_string());
+            }
+        }
+    }
+
+    pub fn extend<'a, I>(&mut self, table: Table, pairs: I)
+    where
+        I: IntoIterator<Item = (&'a str, &'a str)>,
+    {
+        for (from, to) in pairs {
+            self.insert(table, from, to);
+        }
+    }
+
+    // Remove every mapping of a table, in both directions
+    pub fn clear(&mut self, table: Table) {
+        match table {
+            Table::Romaji => {
+                self.romaji_to_hiragana.clear();
+                self.hiragana_to_romaji.clear();
+                self.finals.clear();
+            }
+            Table::Geminates => self.geminates_to_hiragana.clear(),
+            Table::Kana => {
+                self.katakana_to_hiragana.clear();
+                self.hiragana_to_katakana.clear();
+            }
+        }
+    }
+
+    // Load mappings from tab-separated lines of `from<TAB>to`, with an optional
+    // third column `alias` for one-way mappings. Blank lines and lines starting
+    // with # are skipped. Nothing is loaded if any line is invalid.
+    pub fn load_tsv(&mut self, table: Table, tsv: &str) -> Result<(), Error> {
+        let mut rows = Vec::new();
+
+        for (index, line) in tsv.lines().enumerate() {
+            let line = line.trim_end_matches('\r');
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let invalid = |reason: &str| Error::InvalidTable {
+                line: index + 1,
+                reason: reason.to_string(),
+            };
+
+            let columns: Vec<&str> = line.split('\t').collect();
+            let alias = match columns.get(2) {
+                None => false,
+                Some(&"alias") => true,
+                Some(_) => return Err(invalid("third column must be \"alias\"")),
+            };
+
+            match columns[..] {
+                [from, to, ..] if columns.len() <= 3 && !from.is_empty() && !to.is_empty() => {
+                    rows.push((from, to, alias))
+                }
+                _ => return Err(invalid("expected two non-empty tab-separated columns")),
+            }
+        }
+
+        for (from, to, alias) in rows {
+            if alias {
+                self.alias(table, from, to);
+            } else {
+                self.insert(table, from, to);
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn load_tsv_file<P: AsRef<Path>>(&mut self, table: Table, path: P) -> Result<(), Error> {
+        let tsv = fs::read_to_string(path).map_err(|e| Error::UnreadableTable(e.to_string()))?;
+
+        self.load_tsv(table, &tsv)
+    }
+
+    // Load mappings from a JSON object of `{"from": "to"}`
+    #[cfg(feature = "json")]
+    pub fn load_json(&mut self, table: Table, json: &str) -> Result<(), Error> {
+        let invalid = |e: serde_json::Error| Error::InvalidTable {
+            line: e.line(),
+            reason: e.to_string(),
+        };
+        let pairs: HashMap<String, String> = serde_json::from_str(json).map_err(invalid)?;
+
+        self.extend(
+            table,
+            pairs.iter().map(|(from, to)| (from.as_str(), to.as_str())),
+        );
+
+        Ok(())
+    }
+
+    // Katakana chars in `kana` replaced with their hiragana
+    pub(crate) fn hiragana(&self, kana: &str) -> String {
+        kana.chars()
+            .map(|ch| {
+                self.katakana_to_hiragana
+                    .get(ch.to_string().as_str())
+                    .cloned()
+                    .unwrap_or_else(|| ch.to_string())
+            })
+            .collect()
+    }
+
+    // Longest key (in chars) of a table, to bound longest-match lookups
+    pub(crate) fn max_key_len(map: &HashMap<String, String>) -> usize {
+        map.keys().map(|key| key.chars().count()).max().unwrap_or(0)
+    }
+}
+
+fn to_owned(map: &phf::Map<&str, &str>) -> HashMap<String, String> {
+    map.entries()
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::converter::{Converter, Target};
+
+    fn romaji(tables: Tables) -> Converter {
+        Converter::builder()
+            .target(Target::Romaji)
+            .tables(tables)
+            .build()
+    }
+
+    #[test]
+    fn test_layered_over_builtin() {
+        let mut tables = Tables::builtin();
+        tables.alias(Table::Romaji, "tsy", "つ");
+        tables.insert(Table::Romaji, "si", "し");
+
+        let hiragana = Converter::builder().tables(tables.clone()).build();
+        assert_eq!(hiragana.convert("tsykue"), "つくえ");
+        assert_eq!(romaji(tables.clone()).convert("つくえ"), "tsukue");
+        assert_eq!(romaji(tables).convert("すし"), "susi");
+    }
+
+    #[test]
+    fn test_replace_builtin() {
+        let mut tables = Tables::builtin();
+        tables.clear(Table::Romaji);
+        tables.extend(Table::Romaji, vec![("ka", "か"), ("na", "な")]);
+
+        let hiragana = Converter::builder().tables(tables.clone()).build();
+        assert_eq!(hiragana.convert("kana"), "かな");
+        assert_eq!(hiragana.convert("kasa"), "かsa");
+        assert_eq!(romaji(tables).convert("カナ"), "kana");
+    }
+
+    #[test]
+    fn test_load_tsv() {
+        let mut tables = Tables::builtin();
+        let tsv = "# company aliases\nkwa\tくゎ\n\nxtsu\tっ\talias\n";
+        assert_eq!(tables.load_tsv(Table::Romaji, tsv), Ok(()));
+        assert_eq!(romaji(tables.clone()).convert("くゎ"), "kwa");
+
+        let invalid = tables.load_tsv(Table::Romaji, "ka\tか\nki\n");
+        assert_eq!(
+            invalid,
+            Err(Error::InvalidTable {
+                line: 2,
+                reason: "expected two non-empty tab-separated columns".to_string()
+            })
+        );
+        assert!(tables.load_tsv(Table::Romaji, "ka\tか\tboth").is_err());
+        assert!(tables.load_tsv_file(Table::Romaji, "missing.tsv").is_err());
+    }
+
+    #[test]
+    fn test_hentaigana() {
+        let hentaigana = "\u{1B002}\u{1B017}\u{1B062}";
+        assert_eq!(romaji(Tables::builtin()).convert(hentaigana), "akachi");
+        assert_eq!(Converter::default().convert(hentaigana), "あかち");
+    }
+
+    #[test]
+    fn test_ainu() {
+        let mut tables = Tables::builtin();
+        tables.add_ainu();
+
+        let katakana = Converter::builder()
+            .target(Target::Katakana)
+            .tables(tables.clone())
+            .build();
+        assert_eq!(katakana.convert("pirka"), "ピㇼカ");
+        assert_eq!(katakana.convert("kar"), "カㇻ");
+        assert_eq!(katakana.convert("kus sas"), "クㇲ サㇱ");
+        assert_eq!(katakana.convert("ap"), "アㇷ゚");
+        assert_eq!(katakana.convert("aynu kamuy"), "アイヌ カムイ");
+        assert_eq!(katakana.convert("awa"), "アワ");
+
+        let romaji = romaji(tables);
+        assert_eq!(romaji.convert("ピㇼカ"), "pirka");
+        assert_eq!(romaji.convert("カㇺ アㇷ゚ シㇱ"), "kam ap shis");
+        assert_eq!(romaji.convert("オㇿ"), "or");
+        assert_eq!(self::romaji(Tables::builtin()).convert("ピㇼカ"), "piㇼka");
+
+        // ㇲ after a is read back by its consonant, as ㇱ
+        assert_eq!(katakana.convert(&romaji.convert("アㇲ")), "アㇱ");
+
+        for word in ["アイヌ", "カムイ", "ピㇼカ", "クㇲ", "カㇺ"] {
+            assert_eq!(katakana.convert(&romaji.convert(word)), word);
+        }
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_load_json() {
+        let mut tables = Tables::builtin();
+        assert_eq!(
+            tables.load_json(Table::Romaji, r#"{"kwa": "くゎ"}"#),
+            Ok(())
+        );
+        assert_eq!(romaji(tables.clone()).convert("くゎ"), "kwa");
+        assert!(tables.load_json(Table::Romaji, "[1, 2]").is_err());
+    }
+}
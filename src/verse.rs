@@ -0,0 +1,84 @@
+use crate::mora::count_morae;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Form {
+    Haiku,
+    Tanka,
+}
+
+impl Form {
+    // Expected morae per line
+    pub fn pattern(self) -> &'static [usize] {
+        match self {
+            Form::Haiku => &[5, 7, 5],
+            Form::Tanka => &[5, 7, 5, 7, 7],
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Verse {
+    pub form: Form,
+    // Morae counted on each line of the input
+    pub counts: Vec<usize>,
+}
+
+impl Verse {
+    pub fn is_valid(&self) -> bool {
+        self.counts == self.form.pattern()
+    }
+
+    // Indexes of the lines whose count doesn't match the form (including missing lines)
+    pub fn mismatched_lines(&self) -> Vec<usize> {
+        let pattern = self.form.pattern();
+
+        (0..pattern.len().max(self.counts.len()))
+            .filter(|&i| self.counts.get(i) != pattern.get(i))
+            .collect()
+    }
+}
+
+// Count the morae of each line of a poem and check it against `form`.
+//
+// Lines are separated by newlines or "/", blank lines are ignored.
+pub fn check(input: &str, form: Form) -> Verse {
+    let counts = input
+        .split(['\n', '/'])
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(count_morae)
+        .collect();
+
+    Verse { form, counts }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_haiku() {
+        let verse = check("ふるいけや\nかわずとびこむ\nみずのおと", Form::Haiku);
+        assert_eq!(verse.counts, vec![5, 7, 5]);
+        assert!(verse.is_valid());
+
+        let verse = check("furuike ya / kawazu tobikomu / mizu no oto", Form::Haiku);
+        assert_eq!(verse.counts, vec![5, 7, 5]);
+        assert!(verse.is_valid());
+
+        let verse = check("ふるいけ\nかわずとびこむ", Form::Haiku);
+        assert!(!verse.is_valid());
+        assert_eq!(verse.mismatched_lines(), vec![0, 2]);
+    }
+
+    #[test]
+    fn test_check_tanka() {
+        let verse = check(
+            "しらとりは/かなしからずや/そらのあお/うみのあおにも/そまずただよう",
+            Form::Tanka,
+        );
+        assert_eq!(verse.counts, vec![5, 7, 5, 7, 7]);
+        assert!(verse.is_valid());
+        assert!(!check("しらとりは/かなしからずや/そらのあお", Form::Tanka).is_valid());
+    }
+}
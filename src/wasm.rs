@@ -0,0 +1,69 @@
+// JavaScript bindings, built with `wasm-pack build -- --features wasm`.
+//
+// wasm-bindgen generates the TypeScript typings (konj.d.ts) alongside the JS glue,
+// with camelCase names: toHiragana, toKatakana, toRomaji and detectScript.
+use crate::lexer::LexItem;
+use crate::script;
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen(js_name = toHiragana)]
+pub fn to_hiragana(input: &str) -> String {
+    crate::to_hiragana(input)
+}
+
+#[wasm_bindgen(js_name = toKatakana)]
+pub fn to_katakana(input: &str) -> String {
+    crate::to_katakana(input)
+}
+
+#[wasm_bindgen(js_name = toRomaji)]
+pub fn to_romaji(input: &str) -> String {
+    crate::to_romaji(input)
+}
+
+#[wasm_bindgen(js_name = detectScript)]
+pub fn detect_script(input: &str) -> Script {
+    Script(script::detect(input))
+}
+
+// script::Script, exposed to JS as an opaque class
+#[wasm_bindgen]
+pub struct Script(script::Script);
+
+#[wasm_bindgen]
+impl Script {
+    pub fn count(&self, item: LexItem) -> usize {
+        self.0.count(item)
+    }
+
+    pub fn total(&self) -> usize {
+        self.0.total()
+    }
+
+    pub fn percentage(&self, item: LexItem) -> f64 {
+        self.0.percentage(item)
+    }
+
+    pub fn confidence(&self, item: LexItem) -> f64 {
+        self.0.confidence(item)
+    }
+
+    pub fn dominant(&self) -> Option<LexItem> {
+        self.0.dominant()
+    }
+
+    #[wasm_bindgen(js_name = isOnly)]
+    pub fn is_only(&self, item: LexItem) -> bool {
+        self.0.is_only(item)
+    }
+
+    #[wasm_bindgen(js_name = isNeutral)]
+    pub fn is_neutral(&self) -> bool {
+        self.0.is_neutral()
+    }
+
+    #[wasm_bindgen(js_name = isMixed)]
+    pub fn is_mixed(&self) -> bool {
+        self.0.is_mixed()
+    }
+}
@@ -0,0 +1,84 @@
+// Compiles tests/ffi/test.c against the cdylib and the generated konj.h, then runs it.
+// Run with `cargo test --features ffi`.
+#![cfg(feature = "ffi")]
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+// Build the cdylib with the ffi feature into its own target dir. The one cargo
+// test leaves in target/ may be from a build without the feature.
+fn build_cdylib() -> PathBuf {
+    let exe = env::current_exe().unwrap();
+    let target_dir = exe.ancestors().nth(3).unwrap().join("ffi");
+    let cargo = env::var("CARGO").unwrap_or_else(|_| String::from("cargo"));
+
+    let status = Command::new(cargo)
+        .args(["build", "--lib", "--features", "ffi", "--target-dir"])
+        .arg(&target_dir)
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .status()
+        .expect("failed to run cargo");
+    assert!(status.success(), "failed to build the cdylib");
+
+    target_dir.join("debug")
+}
+
+// The header build.rs generated in OUT_DIR
+fn header() -> &'static Path {
+    Path::new(env!("KONJ_HEADER"))
+}
+
+// include/konj.h is checked in for C users, regenerate it with
+// `KONJ_UPDATE_HEADER=1 cargo test --features ffi`
+#[test]
+fn test_header_up_to_date() {
+    let checked_in = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("include")
+        .join("konj.h");
+    let generated = fs::read_to_string(header()).unwrap();
+
+    if env::var_os("KONJ_UPDATE_HEADER").is_some() {
+        fs::write(&checked_in, &generated).unwrap();
+    }
+
+    assert!(
+        fs::read_to_string(&checked_in).unwrap() == generated,
+        "include/konj.h is out of date, run `KONJ_UPDATE_HEADER=1 cargo test --features ffi`"
+    );
+}
+
+#[test]
+fn test_c_program() {
+    let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let lib_dir = build_cdylib();
+    let program = lib_dir.join("konj_ffi_test");
+    let cc = env::var("CC").unwrap_or_else(|_| String::from("cc"));
+
+    let status = Command::new(cc)
+        .arg(manifest_dir.join("tests").join("ffi").join("test.c"))
+        .arg("-I")
+        .arg(header().parent().unwrap())
+        .arg("-L")
+        .arg(&lib_dir)
+        .arg(format!("-Wl,-rpath,{}", lib_dir.display()))
+        .arg("-lkonj")
+        .arg("-o")
+        .arg(&program)
+        .status()
+        .expect("failed to run the C compiler");
+    assert!(status.success(), "failed to compile tests/ffi/test.c");
+
+    // cargo test points the library path at target/<profile>/deps, which would win over the rpath
+    let output = Command::new(&program)
+        .env("LD_LIBRARY_PATH", &lib_dir)
+        .env("DYLD_LIBRARY_PATH", &lib_dir)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
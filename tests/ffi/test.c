@@ -0,0 +1,60 @@
+/* Exercised by tests/ffi.rs, which compiles this against the konj cdylib. */
+#include <stdio.h>
+#include <string.h>
+
+#include "konj.h"
+
+static int failures = 0;
+
+static void check_conversion(KonjStatus (*convert)(const char *, char **),
+                             const char *input, const char *expected) {
+  char *output = NULL;
+  KonjStatus status = convert(input, &output);
+
+  if (status != KONJ_STATUS_OK || output == NULL || strcmp(output, expected) != 0) {
+    fprintf(stderr, "%s: expected \"%s\", got status %d \"%s\"\n", input, expected, status,
+            output ? output : "(null)");
+    failures++;
+  }
+
+  konj_string_free(output);
+}
+
+static void check_status(KonjStatus actual, KonjStatus expected, const char *what) {
+  if (actual != expected) {
+    fprintf(stderr, "%s: expected status %d, got %d\n", what, expected, actual);
+    failures++;
+  }
+}
+
+int main(void) {
+  check_conversion(konj_to_hiragana, "shinkansen", "しんかんせん");
+  check_conversion(konj_to_katakana, "kippu", "キップ");
+  check_conversion(konj_to_romaji, "こんじゅ が すごい だ", "konju ga sugoi da");
+
+  char *output = NULL;
+  check_status(konj_to_romaji("けしゴム", &output), KONJ_STATUS_UNKNOWN_SCRIPT, "mixed script");
+  check_status(output == NULL ? KONJ_STATUS_OK : KONJ_STATUS_UNKNOWN_SCRIPT, KONJ_STATUS_OK,
+               "output on error");
+  check_status(konj_to_romaji(NULL, &output), KONJ_STATUS_NULL_POINTER, "null input");
+  check_status(konj_to_romaji("kana", NULL), KONJ_STATUS_NULL_POINTER, "null output");
+  check_status(konj_to_romaji("\xff\xfe", &output), KONJ_STATUS_INVALID_UTF8, "invalid utf-8");
+
+  KonjScript script;
+  check_status(konj_detect_script("けしゴム", &script), KONJ_STATUS_OK, "detect");
+  if (script.total != 4 || !script.is_mixed || script.counts[KONJ_LEX_ITEM_KATAKANA] != 2 ||
+      script.percentages[KONJ_LEX_ITEM_HIRAGANA] != 50.0) {
+    fprintf(stderr, "detect: unexpected breakdown\n");
+    failures++;
+  }
+
+  check_status(konj_detect_script("kippu!", &script), KONJ_STATUS_OK, "detect romaji");
+  if (script.dominant != KONJ_LEX_ITEM_ROM || script.is_neutral || script.is_mixed) {
+    fprintf(stderr, "detect romaji: unexpected breakdown\n");
+    failures++;
+  }
+
+  konj_string_free(NULL);
+
+  return failures == 0 ? 0 : 1;
+}
@@ -0,0 +1,77 @@
+# Mirrors the unit tests in src/lib.rs, src/lexer.rs and src/script.rs.
+# Run with `maturin develop && pytest`.
+import pytest
+
+import konj
+from konj import LexItem
+
+
+def test_to_hiragana():
+    assert konj.to_hiragana("shinkansen") == "しんかんせん"
+    assert konj.to_hiragana("はは") == "はは"
+    assert konj.to_hiragana("doki") == "どき"
+    assert konj.to_hiragana("kippu") == "きっぷ"
+    assert konj.to_hiragana("きっう") == "きっう"
+
+
+def test_to_katakana():
+    assert konj.to_katakana("shinkansen") == "シンカンセン"
+    assert konj.to_katakana("はは") == "ハハ"
+    assert konj.to_katakana("doki") == "ドキ"
+    assert konj.to_katakana("kippu") == "キップ"
+    assert konj.to_katakana("きっう") == "キッウ"
+
+
+def test_to_romaji():
+    assert konj.to_romaji("shinkansen") == "shinkansen"
+    assert konj.to_romaji("はは") == "haha"
+    assert konj.to_romaji("ドキ") == "doki"
+    assert konj.to_romaji("きっぷ") == "kippu"
+    assert konj.to_romaji("キップ") == "kippu"
+    assert konj.to_romaji("きっう") == "ki'u"
+    assert konj.to_romaji("こんじゅ が すごい だ") == "konju ga sugoi da"
+    assert konj.to_romaji("コンジュ ガ スゴイ ダ") == "konju ga sugoi da"
+
+
+def test_neutral_input():
+    assert konj.to_hiragana("   ") == "   "
+    assert konj.to_katakana("123 !!") == "123 !!"
+    assert konj.to_romaji("123 !!") == "123 !!"
+
+
+def test_mixed_input():
+    assert konj.to_romaji("けしゴム") == "Did not understand input character set."
+    assert konj.to_hiragana("kippu 2") == "きっぷ 2"
+    assert konj.try_to_katakana("けし") == "ケシ"
+
+    with pytest.raises(ValueError):
+        konj.try_to_katakana("けしゴム")
+
+
+def test_lex():
+    assert konj.lex("けしゴム") == ["けし", "ゴム"]
+    assert konj.lex("こんじゅ が") == ["こんじゅ", " ", "が"]
+    assert konj.lex("kippu!!") == ["kippu", "!!"]
+
+
+def test_parse():
+    assert konj.parse(" ") == LexItem.Space
+    assert konj.parse("。") == LexItem.Punct
+    assert konj.parse("k") == LexItem.Rom
+    assert konj.parse("7") == LexItem.Num
+
+
+def test_detect():
+    script = konj.detect("けしゴム")
+    assert script.is_mixed()
+    assert script.percentage(LexItem.Hiragana) == 50.0
+    assert script.percentage(LexItem.Katakana) == 50.0
+
+    assert konj.detect("123 !!").is_neutral()
+    assert konj.detect("123 !!").dominant() is None
+    assert konj.detect("kippu, please!").is_only(LexItem.Rom)
+    assert konj.detect("ab か").breakdown() == [
+        (LexItem.Rom, 50.0),
+        (LexItem.Hiragana, 25.0),
+        (LexItem.Space, 25.0),
+    ]
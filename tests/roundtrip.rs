@@ -0,0 +1,139 @@
+// Property tests: every kana sequence the tables support survives a round trip
+// through the other scripts. Run with `cargo test --test roundtrip`.
+#![cfg(not(target_arch = "wasm32"))]
+
+use konj::converter::{Converter, DanglingSokuon, Fragment, Target};
+use konj::error::Error;
+use konj::kana::hiragana_char;
+use konj::{GOJUON, HIRAGANA_TO_ROMAJI};
+use proptest::prelude::*;
+use proptest::sample::select;
+use std::sync::OnceLock;
+
+// Hiragana written by the romaji tables with its romaji, leaving out punctuation,
+// "んn" (typed as "nnn") and っ, which is generated in front of a consonant instead
+fn romanizable() -> Vec<(String, &'static str)> {
+    let mut kana: Vec<(String, &str)> = HIRAGANA_TO_ROMAJI
+        .entries()
+        .filter(|(kana, romaji)| {
+            romaji.starts_with(|ch: char| ch.is_ascii_alphabetic())
+                && !kana.contains(|ch: char| ch.is_ascii())
+        })
+        .map(|(kana, romaji)| (kana.chars().map(hiragana_char).collect(), *romaji))
+        .collect();
+
+    kana.sort_unstable();
+    kana
+}
+
+// Romaji that っ can't be written in front of by doubling. Before a vowel or n it
+// is a dangling sokuon, and "mm" is read as ん (さんま, "samma"). These don't
+// round trip, see unsupported_sokuon.
+const NO_SOKUON: &str = "aiueonm";
+
+// A hiragana string of up to 12 morae, with っ in front of morae that can take it
+fn hiragana() -> impl Strategy<Value = String> {
+    prop::collection::vec((any::<bool>(), select(romanizable())), 0..12).prop_map(|morae| {
+        morae
+            .into_iter()
+            .map(|(sokuon, (kana, romaji))| {
+                if sokuon && romaji.starts_with(|ch: char| !NO_SOKUON.contains(ch)) {
+                    format!("っ{}", kana)
+                } else {
+                    kana
+                }
+            })
+            .collect()
+    })
+}
+
+fn gojuon() -> impl Strategy<Value = String> {
+    prop::collection::vec(select(GOJUON), 0..12).prop_map(|kana| kana.concat())
+}
+
+// Converters are built once, building their tables per case is slow
+fn converter(target: Target) -> &'static Converter {
+    static CONVERTERS: OnceLock<[Converter; 3]> = OnceLock::new();
+
+    let converters = CONVERTERS.get_or_init(|| {
+        [Target::Hiragana, Target::Katakana, Target::Romaji]
+            .map(|target| Converter::builder().target(target).build())
+    });
+
+    &converters[target as usize]
+}
+
+proptest! {
+    // More cases than the default 256, rare pairs like んな need them
+    #![proptest_config(ProptestConfig::with_cases(2000))]
+
+    #[test]
+    fn katakana_round_trip(hiragana in gojuon()) {
+        prop_assert_eq!(konj::to_hiragana(&konj::to_katakana(&hiragana)), hiragana.clone());
+
+        let katakana = converter(Target::Katakana).convert(&hiragana);
+        prop_assert_eq!(converter(Target::Hiragana).convert(&katakana), hiragana);
+    }
+
+    #[test]
+    fn romaji_round_trip(hiragana in hiragana()) {
+        let romaji = converter(Target::Romaji);
+        let written = romaji.convert(&hiragana);
+
+        prop_assert_eq!(converter(Target::Hiragana).convert(&written), hiragana.clone());
+        prop_assert_eq!(romaji.convert(&converter(Target::Katakana).convert(&hiragana)), written);
+    }
+
+    #[test]
+    fn free_functions_round_trip(hiragana in hiragana()) {
+        let romaji = konj::to_romaji(&hiragana);
+
+        prop_assert_eq!(konj::to_hiragana(&romaji), hiragana.clone());
+        prop_assert_eq!(konj::to_romaji(&konj::to_katakana(&hiragana)), romaji);
+    }
+
+    #[test]
+    fn alignment_covers_input_and_output(input in "\\PC{0,16}", target in 0..3usize) {
+        let aligned = converter([Target::Hiragana, Target::Katakana, Target::Romaji][target])
+            .convert_aligned(&input);
+        let (mut input_end, mut output_end) = (0, 0);
+
+        for span in aligned.spans.iter() {
+            prop_assert_eq!(span.input.start, input_end);
+            prop_assert_eq!(span.output.start, output_end);
+            input_end = span.input.end;
+            output_end = span.output.end;
+        }
+
+        prop_assert_eq!(input_end, input.chars().count());
+        prop_assert_eq!(output_end, aligned.text.chars().count());
+    }
+}
+
+// っ that romaji can't double is reported in strict mode, or left out
+#[test]
+fn unsupported_sokuon() {
+    let strict = Converter::builder()
+        .target(Target::Romaji)
+        .dangling_sokuon(DanglingSokuon::Unknown)
+        .build();
+    let sokuon = |input| Fragment {
+        text: String::from("っ"),
+        input,
+    };
+
+    assert_eq!(
+        strict.try_convert("きっう きっな"),
+        Err(Error::Unconvertible(vec![sokuon(1..2), sokuon(5..6)]))
+    );
+
+    let omit = Converter::builder()
+        .target(Target::Romaji)
+        .dangling_sokuon(DanglingSokuon::Omit)
+        .build();
+    let hiragana = converter(Target::Hiragana);
+
+    assert_eq!(hiragana.convert(&omit.convert("きっう")), "きう");
+    // "mm" is ん
+    assert_eq!(hiragana.convert(&omit.convert("さっま")), "さんま");
+}
@@ -0,0 +1,34 @@
+// Run under node with `cargo test --target wasm32-unknown-unknown --features wasm`
+// (needs `wasm-bindgen-cli`) or `wasm-pack test --node -- --features wasm`
+#![cfg(all(target_arch = "wasm32", feature = "wasm"))]
+
+use konj::lexer::LexItem;
+use konj::wasm::*;
+use wasm_bindgen_test::*;
+
+#[wasm_bindgen_test]
+fn test_to_hiragana() {
+    assert_eq!(to_hiragana("shinkansen"), "しんかんせん");
+    assert_eq!(to_hiragana("kippu"), "きっぷ");
+}
+
+#[wasm_bindgen_test]
+fn test_to_katakana() {
+    assert_eq!(to_katakana("shinkansen"), "シンカンセン");
+    assert_eq!(to_katakana("はは"), "ハハ");
+}
+
+#[wasm_bindgen_test]
+fn test_to_romaji() {
+    assert_eq!(to_romaji("キップ"), "kippu");
+    assert_eq!(to_romaji("こんじゅ が すごい だ"), "konju ga sugoi da");
+}
+
+#[wasm_bindgen_test]
+fn test_detect_script() {
+    let script = detect_script("けしゴム");
+    assert!(script.is_mixed());
+    assert_eq!(script.percentage(LexItem::Katakana), 50.0);
+    assert!(detect_script("123 !!").is_neutral());
+    assert_eq!(detect_script("kippu").dominant(), Some(LexItem::Rom));
+}